@@ -1,30 +1,102 @@
 use bevy::prelude::Resource;
-// use clap::Parser;
+use clap::Parser;
+
+use color_eyre::eyre::{self, WrapErr};
 
 /// Interactive CommonRoad scenario inspector
-#[derive(Debug, Resource)]
-// #[command(author, version, about, long_about = None)]
+#[derive(Debug, Parser, Resource)]
+#[command(author, version, about, long_about = None)]
 pub struct Args {
     /// Path to the reactive planner logs directory
-    // #[arg(long)]
-    pub logs: std::path::PathBuf,
+    #[arg(long, required_unless_present = "scenario")]
+    pub logs: Option<std::path::PathBuf>,
+
+    /// Path to a scenario file in the Protobuf or XML format (overrides the scenario stored in the logs,
+    /// if only a scenario is given it is shown without any planner data)
+    #[arg(long)]
+    pub scenario: Option<std::path::PathBuf>,
+
+    /// Path to the reference path file (reference_path.json)
+    #[arg(long, alias = "reference_path")]
+    pub reference_path: Option<std::path::PathBuf>,
+
+    /// Follow a planner that is still writing the logs, new time steps are shown as they arrive
+    #[arg(long, requires = "logs")]
+    pub live: bool,
+
+    /// Time step to show on startup, limited to the time steps of the scenario
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(..=i32::MAX as i64))]
+    pub time_step: u32,
+
+    /// Log verbosity
+    #[arg(long, value_enum, default_value_t = LogLevel::Info)]
+    pub log_level: LogLevel,
 }
 
-impl Args {
-    pub(crate) fn parse() -> Self {
-        Self {
-            logs: std::env::args().nth(1).unwrap().into(),
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for bevy::log::Level {
+    fn from(value: LogLevel) -> Self {
+        match value {
+            LogLevel::Error => bevy::log::Level::ERROR,
+            LogLevel::Warn => bevy::log::Level::WARN,
+            LogLevel::Info => bevy::log::Level::INFO,
+            LogLevel::Debug => bevy::log::Level::DEBUG,
+            LogLevel::Trace => bevy::log::Level::TRACE,
         }
     }
 }
 
-/*
+fn check_file(path: &std::path::Path, what: &str) -> eyre::Result<()> {
+    let metadata = std::fs::metadata(path)
+        .wrap_err_with(|| format!("could not access {} {}", what, path.display()))?;
 
-    /// Path to the scenario file
-    #[arg(long)]
-    pub scenario: std::path::PathBuf,
+    if !metadata.is_file() {
+        eyre::bail!("{} {} is not a file", what, path.display());
+    }
 
-    /// Path to the reference path file (reference_path.json)
-    #[arg(long)]
-    pub reference_path: std::path::PathBuf,
-*/
+    Ok(())
+}
+
+impl Args {
+    /// Checks that all paths passed on the command line exist,
+    /// so that loading fails early with a readable error instead of a panic.
+    pub(crate) fn validate(&self) -> eyre::Result<()> {
+        if let Some(logs) = &self.logs {
+            let metadata = std::fs::metadata(logs)
+                .wrap_err_with(|| format!("could not access logs directory {}", logs.display()))?;
+
+            if !metadata.is_dir() {
+                eyre::bail!("logs path {} is not a directory", logs.display());
+            }
+        }
+
+        if let (None, Some(db_path)) = (&self.scenario, self.trajectories_db()) {
+            check_file(&db_path, "trajectories database")
+                .wrap_err("the logs directory does not contain a scenario, pass one using --scenario")?;
+        }
+
+        if let Some(scenario) = &self.scenario {
+            check_file(scenario, "scenario file")?;
+        }
+
+        if let Some(reference_path) = &self.reference_path {
+            check_file(reference_path, "reference path file")?;
+        }
+
+        Ok(())
+    }
+
+    /// Path to the `trajectories.db` in the logs directory,
+    /// `None` if only a standalone scenario was given.
+    pub(crate) fn trajectories_db(&self) -> Option<std::path::PathBuf> {
+        self.logs.as_ref().map(|logs| logs.join("trajectories.db"))
+    }
+}
@@ -0,0 +1,1230 @@
+//! Reader for CommonRoad XML scenarios (format version 2020a).
+//!
+//! The XML document is converted into the same [`commonroad_pb::CommonRoad`] structure
+//! that is decoded from Protobuf scenarios, so the rest of Corroscope does not need to
+//! know which format a scenario was loaded from.
+
+use crate::commonroad_pb;
+
+use commonroad_pb::{float_exact_or_interval, integer_exact_or_interval};
+
+type Node<'a, 'input> = roxmltree::Node<'a, 'input>;
+
+#[derive(Debug)]
+pub enum XmlError {
+    Parse(roxmltree::Error),
+    MissingElement {
+        parent: String,
+        name: &'static str,
+        position: roxmltree::TextPos,
+    },
+    MissingAttribute {
+        element: String,
+        name: &'static str,
+        position: roxmltree::TextPos,
+    },
+    InvalidValue {
+        element: String,
+        value: String,
+        position: roxmltree::TextPos,
+    },
+}
+
+impl std::error::Error for XmlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XmlError::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for XmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XmlError::Parse(err) => write!(f, "invalid XML: {}", err),
+            XmlError::MissingElement { parent, name, position } => {
+                write!(f, "<{}> at {} is missing child element <{}>", parent, position, name)
+            }
+            XmlError::MissingAttribute { element, name, position } => {
+                write!(f, "<{}> at {} is missing attribute '{}'", element, position, name)
+            }
+            XmlError::InvalidValue { element, value, position } => {
+                write!(f, "<{}> at {} has invalid value '{}'", element, position, value)
+            }
+        }
+    }
+}
+
+impl From<roxmltree::Error> for XmlError {
+    fn from(value: roxmltree::Error) -> Self {
+        XmlError::Parse(value)
+    }
+}
+
+fn position(node: Node) -> roxmltree::TextPos {
+    node.document().text_pos_at(node.range().start)
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.is_element() && child.has_tag_name(name))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn required_child<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> Result<Node<'a, 'input>, XmlError> {
+    child(node, name).ok_or_else(|| XmlError::MissingElement {
+        parent: node.tag_name().name().to_owned(),
+        name,
+        position: position(node),
+    })
+}
+
+fn invalid_value(node: Node, value: &str) -> XmlError {
+    XmlError::InvalidValue {
+        element: node.tag_name().name().to_owned(),
+        value: value.to_owned(),
+        position: position(node),
+    }
+}
+
+fn text<'a>(node: Node<'a, '_>) -> &'a str {
+    node.text().unwrap_or_default().trim()
+}
+
+fn parse_text<T: std::str::FromStr>(node: Node) -> Result<T, XmlError> {
+    let value = text(node);
+    value.parse().map_err(|_| invalid_value(node, value))
+}
+
+fn parse_bool(node: Node) -> Result<bool, XmlError> {
+    let value = text(node);
+    match value.to_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(invalid_value(node, value)),
+    }
+}
+
+fn parse_child<T: std::str::FromStr>(node: Node, name: &'static str) -> Result<T, XmlError> {
+    parse_text(required_child(node, name)?)
+}
+
+fn parse_optional_child<T: std::str::FromStr>(
+    node: Node,
+    name: &'static str,
+) -> Result<Option<T>, XmlError> {
+    child(node, name).map(parse_text).transpose()
+}
+
+fn attribute<T: std::str::FromStr>(node: Node, name: &'static str) -> Result<T, XmlError> {
+    let value = node.attribute(name).ok_or_else(|| XmlError::MissingAttribute {
+        element: node.tag_name().name().to_owned(),
+        name,
+        position: position(node),
+    })?;
+
+    value.parse().map_err(|_| invalid_value(node, value))
+}
+
+fn id(node: Node) -> Result<u32, XmlError> {
+    attribute(node, "id")
+}
+
+/// Collects the `ref` attributes of all children with the given name.
+fn refs(node: Node, name: &'static str) -> Result<Vec<u32>, XmlError> {
+    children(node, name).map(|r| attribute(r, "ref")).collect()
+}
+
+/// Converts XML enum values (`parkedVehicle`, `broad_dashed`, `CutIn`, ...)
+/// to the Protobuf enum value names (`PARKED_VEHICLE`, `BROAD_DASHED`, `CUT_IN`, ...).
+fn screaming_snake_case(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 4);
+    let mut prev_lower = false;
+
+    for c in value.chars() {
+        if c == '_' || c == '-' || c == ' ' {
+            result.push('_');
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            result.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        result.extend(c.to_uppercase());
+    }
+
+    result
+}
+
+fn parse_enum_str<E: Into<i32>>(
+    node: Node,
+    value: &str,
+    from_str_name: fn(&str) -> Option<E>,
+) -> Result<i32, XmlError> {
+    let name = screaming_snake_case(value);
+
+    // Spellings that differ between the XML and the Protobuf format
+    let name = match name.as_str() {
+        "ILLEGAL_CUT_IN" => "ILLEGAL_CUTIN",
+        "HEAVY_RAIN" => "HEAVY_AIN",
+        name => name,
+    };
+
+    from_str_name(name)
+        .map(Into::into)
+        .ok_or_else(|| invalid_value(node, value))
+}
+
+fn parse_enum<E: Into<i32>>(node: Node, from_str_name: fn(&str) -> Option<E>) -> Result<i32, XmlError> {
+    parse_enum_str(node, text(node), from_str_name)
+}
+
+fn parse_point(node: Node) -> Result<commonroad_pb::Point, XmlError> {
+    Ok(commonroad_pb::Point {
+        x: parse_child(node, "x")?,
+        y: parse_child(node, "y")?,
+    })
+}
+
+fn parse_points(node: Node) -> Result<Vec<commonroad_pb::Point>, XmlError> {
+    children(node, "point").map(parse_point).collect()
+}
+
+fn parse_optional_point(node: Node, name: &'static str) -> Result<Option<commonroad_pb::Point>, XmlError> {
+    // Positions are wrapped in a <position><point>...</point></position> element
+    child(node, name)
+        .map(|position| match child(position, "point") {
+            Some(point) => parse_point(point),
+            None => parse_point(position),
+        })
+        .transpose()
+}
+
+fn parse_simple_shape(node: Node) -> Result<Option<commonroad_pb::shape::Shape>, XmlError> {
+    use commonroad_pb::shape::Shape;
+
+    let shape = match node.tag_name().name() {
+        "rectangle" => Shape::Rectangle(commonroad_pb::Rectangle {
+            length: parse_child(node, "length")?,
+            width: parse_child(node, "width")?,
+            center: child(node, "center").map(parse_point).transpose()?,
+            orientation: parse_optional_child(node, "orientation")?,
+        }),
+        "circle" => Shape::Circle(commonroad_pb::Circle {
+            radius: parse_child(node, "radius")?,
+            center: child(node, "center").map(parse_point).transpose()?,
+        }),
+        "polygon" => Shape::Polygon(commonroad_pb::Polygon {
+            vertices: parse_points(node)?,
+        }),
+        _ => {
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(shape))
+}
+
+/// Parses all shapes contained in `node`, multiple shapes are combined into a shape group.
+fn parse_shapes(node: Node) -> Result<Option<commonroad_pb::Shape>, XmlError> {
+    let mut shapes = node
+        .children()
+        .filter(Node::is_element)
+        .filter_map(|child| parse_simple_shape(child).transpose())
+        .map(|shape| shape.map(|shape| commonroad_pb::Shape { shape: Some(shape) }))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let shape = match shapes.len() {
+        0 => None,
+        1 => shapes.pop(),
+        _ => Some(commonroad_pb::Shape {
+            shape: Some(commonroad_pb::shape::Shape::ShapeGroup(commonroad_pb::ShapeGroup { shapes })),
+        }),
+    };
+
+    Ok(shape)
+}
+
+fn parse_shape(node: Node) -> Result<commonroad_pb::Shape, XmlError> {
+    parse_shapes(node)?.ok_or_else(|| invalid_value(node, "no shape"))
+}
+
+fn parse_float_exact_or_interval(node: Node) -> Result<commonroad_pb::FloatExactOrInterval, XmlError> {
+    use float_exact_or_interval::ExactOrInterval;
+
+    let exact_or_interval = if let Some(exact) = child(node, "exact") {
+        ExactOrInterval::Exact(parse_text(exact)?)
+    } else {
+        ExactOrInterval::Interval(commonroad_pb::FloatInterval {
+            start: parse_child(node, "intervalStart")?,
+            end: parse_child(node, "intervalEnd")?,
+        })
+    };
+
+    Ok(commonroad_pb::FloatExactOrInterval {
+        exact_or_interval: Some(exact_or_interval),
+    })
+}
+
+fn parse_integer_exact_or_interval(node: Node) -> Result<commonroad_pb::IntegerExactOrInterval, XmlError> {
+    use integer_exact_or_interval::ExactOrInterval;
+
+    let exact_or_interval = if let Some(exact) = child(node, "exact") {
+        ExactOrInterval::Exact(parse_text(exact)?)
+    } else {
+        ExactOrInterval::Interval(commonroad_pb::IntegerInterval {
+            start: parse_child(node, "intervalStart")?,
+            end: parse_child(node, "intervalEnd")?,
+        })
+    };
+
+    Ok(commonroad_pb::IntegerExactOrInterval {
+        exact_or_interval: Some(exact_or_interval),
+    })
+}
+
+/// Parses a state, returning the ids of any lanelets its position refers to
+/// (only used by goal states).
+fn parse_state_with_lanelets(node: Node) -> Result<(commonroad_pb::State, Vec<u32>), XmlError> {
+    let mut state = commonroad_pb::State {
+        time_step: child(node, "time")
+            .map(parse_integer_exact_or_interval)
+            .transpose()?
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let mut lanelets = vec![];
+
+    if let Some(position) = child(node, "position") {
+        lanelets = refs(position, "lanelet")?;
+
+        state.position = if let Some(point) = child(position, "point") {
+            Some(commonroad_pb::state::Position::Point(parse_point(point)?))
+        } else {
+            parse_shapes(position)?.map(commonroad_pb::state::Position::Shape)
+        };
+    }
+
+    macro_rules! float_fields {
+        ($($field:ident => $name:literal),* $(,)?) => {
+            $(
+                state.$field = child(node, $name)
+                    .map(parse_float_exact_or_interval)
+                    .transpose()?;
+            )*
+        };
+    }
+
+    float_fields! {
+        orientation => "orientation",
+        velocity => "velocity",
+        steering_angle => "steeringAngle",
+        steering_angle_speed => "steeringAngleSpeed",
+        yaw_rate => "yawRate",
+        slip_angle => "slipAngle",
+        roll_angle => "rollAngle",
+        roll_rate => "rollRate",
+        pitch_angle => "pitchAngle",
+        pitch_rate => "pitchRate",
+        velocity_y => "velocityY",
+        position_z => "positionZ",
+        velocity_z => "velocityZ",
+        roll_angle_front => "rollAngleFront",
+        roll_rate_front => "rollRateFront",
+        velocity_y_front => "velocityYFront",
+        position_z_front => "positionZFront",
+        velocity_z_front => "velocityZFront",
+        roll_angle_rear => "rollAngleRear",
+        roll_rate_rear => "rollRateRear",
+        velocity_y_rear => "velocityYRear",
+        position_z_rear => "positionZRear",
+        velocity_z_rear => "velocityZRear",
+        front_wheel_angular_speed => "frontWheelAngularSpeed",
+        rear_wheel_angular_speed => "rearWheelAngularSpeed",
+        left_front_wheel_angular_speed => "leftFrontWheelAngularSpeed",
+        right_front_wheel_angular_speed => "rightFrontWheelAngularSpeed",
+        left_rear_wheel_angular_speed => "leftRearWheelAngularSpeed",
+        right_rear_wheel_angular_speed => "rightRearWheelAngularSpeed",
+        delta_y_f => "deltaYf",
+        delta_y_r => "deltaYr",
+        acceleration => "acceleration",
+        acceleration_y => "accelerationY",
+        jerk => "jerk",
+        curvature => "curvature",
+        curvature_rate => "curvatureRate",
+    }
+
+    Ok((state, lanelets))
+}
+
+fn parse_state(node: Node) -> Result<commonroad_pb::State, XmlError> {
+    parse_state_with_lanelets(node).map(|(state, _lanelets)| state)
+}
+
+fn parse_signal_state(node: Node) -> Result<commonroad_pb::SignalState, XmlError> {
+    let flag = |name| child(node, name).map(parse_bool).transpose();
+
+    Ok(commonroad_pb::SignalState {
+        time_step: child(node, "time")
+            .map(parse_integer_exact_or_interval)
+            .transpose()?,
+        horn: flag("horn")?,
+        indicator_left: flag("indicatorLeft")?,
+        indicator_right: flag("indicatorRight")?,
+        braking_lights: flag("brakingLights")?,
+        hazard_warning_lights: flag("hazardWarningLights")?,
+        flashing_blue_lights: flag("flashingBlueLights")?,
+    })
+}
+
+fn parse_signal_series(node: Node) -> Result<Vec<commonroad_pb::SignalState>, XmlError> {
+    let Some(series) = child(node, "signalSeries") else {
+        return Ok(vec![]);
+    };
+
+    children(series, "signalState").map(parse_signal_state).collect()
+}
+
+fn initial_time_step(time_step: &commonroad_pb::IntegerExactOrInterval) -> u32 {
+    match time_step.exact_or_interval {
+        Some(integer_exact_or_interval::ExactOrInterval::Exact(ts)) => ts.max(0) as u32,
+        Some(integer_exact_or_interval::ExactOrInterval::Interval(ref interval)) => {
+            interval.start.max(0) as u32
+        }
+        None => 0,
+    }
+}
+
+fn parse_occupancy_set(node: Node) -> Result<commonroad_pb::SetBasedPrediction, XmlError> {
+    let occupancies = children(node, "occupancy")
+        .map(|occupancy| {
+            Ok(commonroad_pb::Occupancy {
+                time_step: parse_integer_exact_or_interval(required_child(occupancy, "time")?)?,
+                shape: parse_shape(required_child(occupancy, "shape")?)?,
+            })
+        })
+        .collect::<Result<Vec<_>, XmlError>>()?;
+
+    Ok(commonroad_pb::SetBasedPrediction {
+        initial_time_step: occupancies
+            .first()
+            .map(|occ| initial_time_step(&occ.time_step))
+            .unwrap_or_default(),
+        occupancy_set: commonroad_pb::OccupancySet { occupancies },
+    })
+}
+
+fn parse_obstacle_type(node: Node) -> Result<i32, XmlError> {
+    parse_enum(
+        required_child(node, "type")?,
+        commonroad_pb::obstacle_type_enum::ObstacleType::from_str_name,
+    )
+}
+
+fn parse_static_obstacle(node: Node) -> Result<commonroad_pb::StaticObstacle, XmlError> {
+    Ok(commonroad_pb::StaticObstacle {
+        static_obstacle_id: id(node)?,
+        obstacle_type: parse_obstacle_type(node)?,
+        shape: parse_shape(required_child(node, "shape")?)?,
+        initial_state: parse_state(required_child(node, "initialState")?)?,
+        initial_signal_state: child(node, "initialSignalState")
+            .map(parse_signal_state)
+            .transpose()?,
+        signal_series: parse_signal_series(node)?,
+    })
+}
+
+fn parse_dynamic_obstacle(node: Node) -> Result<commonroad_pb::DynamicObstacle, XmlError> {
+    use commonroad_pb::dynamic_obstacle::Prediction;
+
+    let shape = parse_shape(required_child(node, "shape")?)?;
+
+    let prediction = if let Some(trajectory) = child(node, "trajectory") {
+        let states = children(trajectory, "state")
+            .map(parse_state)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Some(Prediction::TrajectoryPrediction(commonroad_pb::TrajectoryPrediction {
+            trajectory: commonroad_pb::Trajectory {
+                initial_time_step: states
+                    .first()
+                    .map(|st| initial_time_step(&st.time_step))
+                    .unwrap_or_default(),
+                states,
+            },
+            shape: shape.clone(),
+        }))
+    } else if let Some(occupancy_set) = child(node, "occupancySet") {
+        Some(Prediction::SetBasedPrediction(parse_occupancy_set(occupancy_set)?))
+    } else {
+        None
+    };
+
+    Ok(commonroad_pb::DynamicObstacle {
+        dynamic_obstacle_id: id(node)?,
+        obstacle_type: parse_obstacle_type(node)?,
+        shape,
+        initial_state: parse_state(required_child(node, "initialState")?)?,
+        prediction,
+        initial_signal_state: child(node, "initialSignalState")
+            .map(parse_signal_state)
+            .transpose()?,
+        signal_series: parse_signal_series(node)?,
+    })
+}
+
+fn parse_environment_obstacle(node: Node) -> Result<commonroad_pb::EnvironmentObstacle, XmlError> {
+    Ok(commonroad_pb::EnvironmentObstacle {
+        environment_obstacle_id: id(node)?,
+        obstacle_type: parse_obstacle_type(node)?,
+        obstacle_shape: parse_shape(required_child(node, "shape")?)?,
+    })
+}
+
+fn parse_phantom_obstacle(node: Node) -> Result<commonroad_pb::PhantomObstacle, XmlError> {
+    Ok(commonroad_pb::PhantomObstacle {
+        obstacle_id: id(node)?,
+        prediction: child(node, "occupancySet")
+            .map(parse_occupancy_set)
+            .transpose()?,
+    })
+}
+
+fn parse_line_marking(node: Node) -> Result<Option<i32>, XmlError> {
+    child(node, "lineMarking")
+        .map(|marking| parse_enum(marking, commonroad_pb::line_marking_enum::LineMarking::from_str_name))
+        .transpose()
+}
+
+fn parse_bound(node: Node) -> Result<commonroad_pb::Bound, XmlError> {
+    Ok(commonroad_pb::Bound {
+        points: parse_points(node)?,
+        line_marking: parse_line_marking(node)?,
+    })
+}
+
+fn parse_stop_line(node: Node) -> Result<commonroad_pb::StopLine, XmlError> {
+    Ok(commonroad_pb::StopLine {
+        points: parse_points(node)?,
+        line_marking: parse_line_marking(node)?.unwrap_or_default(),
+        traffic_sign_refs: refs(node, "trafficSignRef")?,
+        traffic_light_refs: refs(node, "trafficLightRef")?,
+    })
+}
+
+fn parse_adjacent(node: Node, name: &'static str) -> Result<(Option<u32>, Option<i32>), XmlError> {
+    let Some(adjacent) = child(node, name) else {
+        return Ok((None, None));
+    };
+
+    let driving_dir = adjacent
+        .attribute("drivingDir")
+        .map(|dir| {
+            parse_enum_str(adjacent, dir, commonroad_pb::driving_dir_enum::DrivingDir::from_str_name)
+        })
+        .transpose()?;
+
+    Ok((Some(attribute(adjacent, "ref")?), driving_dir))
+}
+
+fn parse_enum_list<E: Into<i32>>(
+    node: Node,
+    name: &'static str,
+    from_str_name: fn(&str) -> Option<E>,
+) -> Result<Vec<i32>, XmlError> {
+    children(node, name)
+        .map(|value| parse_enum(value, from_str_name))
+        .collect()
+}
+
+fn parse_lanelet(node: Node) -> Result<commonroad_pb::Lanelet, XmlError> {
+    use commonroad_pb::{lanelet_type_enum::LaneletType, road_user_enum::RoadUser};
+
+    let (adjacent_left, adjacent_left_dir) = parse_adjacent(node, "adjacentLeft")?;
+    let (adjacent_right, adjacent_right_dir) = parse_adjacent(node, "adjacentRight")?;
+
+    Ok(commonroad_pb::Lanelet {
+        lanelet_id: id(node)?,
+        left_bound: parse_bound(required_child(node, "leftBound")?)?,
+        right_bound: parse_bound(required_child(node, "rightBound")?)?,
+        predecessors: refs(node, "predecessor")?,
+        successors: refs(node, "successor")?,
+        adjacent_left,
+        adjacent_right,
+        adjacent_left_dir,
+        adjacent_right_dir,
+        stop_line: child(node, "stopLine").map(parse_stop_line).transpose()?,
+        lanelet_types: parse_enum_list(node, "laneletType", LaneletType::from_str_name)?,
+        user_one_way: parse_enum_list(node, "userOneWay", RoadUser::from_str_name)?,
+        user_bidirectional: parse_enum_list(node, "userBidirectional", RoadUser::from_str_name)?,
+        traffic_sign_refs: refs(node, "trafficSignRef")?,
+        traffic_light_refs: refs(node, "trafficLightRef")?,
+    })
+}
+
+/// Maps the official (German) traffic sign numbers used in XML files to enum value names.
+fn traffic_sign_name(sign_id: &str) -> Option<&'static str> {
+    let name = match sign_id {
+        "101" => "WARNING_DANGER_SPOT",
+        "102" => "WARNING_RIGHT_BEFORE_LEFT",
+        "103-10" => "WARNING_LEFT_CURVE",
+        "103-20" => "WARNING_RIGHT_CURVE",
+        "108" => "WARNING_STEEP_HILL_DOWNWARDS",
+        "114" => "WARNING_SLIPPERY_ROAD",
+        "123" => "WARNING_CONSTRUCTION_SITE",
+        "124" => "WARNING_TRAFFIC_QUEUES_LIKELY",
+        "125" => "WARNING_ONCOMING_TRAFFIC",
+        "131" => "WARNING_TRAFFIC_LIGHTS_AHEAD",
+        "133-10" => "WARNING_PEDESTRIANS_RIGHT",
+        "133-20" => "WARNING_PEDESTRIANS_LEFT",
+        "138-10" => "WARNING_CROSSING_CYCLIST",
+        "142-10" => "WARNING_ANIMAL_CROSSING_RIGHT",
+        "145-50" => "WARNING_LOOSE_GRAVEL",
+        "201" => "RAILWAY",
+        "205" => "YIELD",
+        "206" => "STOP",
+        "208" => "PRIORITY_OPPOSITE_DIRECTION",
+        "209-10" => "TURN_LEFT_AHEAD",
+        "209-20" => "TURN_RIGHT_AHEAD",
+        "209-30" => "KEEP_STRAIGHT_AHEAD",
+        "211" => "PRESCRIBED_DIRECTION_RIGHT",
+        "215" => "ROUNDABOUT",
+        "220-10" => "ONEWAY_LEFT",
+        "220-20" => "ONEWAY_RIGHT",
+        "222-10" => "PRESCRIBED_PASSING_LEFT",
+        "222-20" => "PRESCRIBED_PASSING_RIGHT",
+        "224" => "BUS_STOP",
+        "237" => "BIKEWAY",
+        "239" => "SIDEWALK",
+        "240" => "PEDESTRIAN_AND_BICYCLE_ROAD",
+        "242.1" => "PEDESTRIAN_ZONE_START",
+        "242.2" => "PEDESTRIAN_ZONE_END",
+        "244.1" => "BICYCLE_ROAD_START",
+        "244.2" => "BICYCLE_ROAD_END",
+        "245" => "BUSLANE",
+        "250" => "BAN_ALL_VEHICLES",
+        "251" => "BAN_CARS",
+        "253" => "BAN_TRUCKS",
+        "254" => "BAN_BICYCLE",
+        "255" => "BAN_MOTORCYCLE",
+        "257-54" => "BAN_BUS",
+        "259" => "BAN_PEDESTRIAN",
+        "260" => "BAN_CAR_TRUCK_BUS_MOTORCYCLE",
+        "261" => "BAN_VEHICLES_CARRYING_DANGEROUS_GOODS",
+        "262" => "MAX_WEIGHT",
+        "264" => "MAX_WIDTH",
+        "265" => "MAX_HEIGHT",
+        "266" => "MAX_LENGTH",
+        "267" => "NO_ENTRY",
+        "270.1" => "ENVIRONMENTAL_ZONE_START",
+        "270.2" => "ENVIRONMENTAL_ZONE_END",
+        "272" => "U_TURN",
+        "274" | "R2-1" => "MAX_SPEED",
+        "274.1" => "MAX_SPEED_ZONE_START",
+        "274.2" => "MAX_SPEED_ZONE_END",
+        "275" => "MIN_SPEED",
+        "276" => "NO_OVERTAKING_START",
+        "277" => "NO_OVERTAKING_TRUCKS_START",
+        "278" => "MAX_SPEED_END",
+        "280" => "NO_OVERTAKING_END",
+        "281" => "NO_OVERTAKING_TRUCKS_END",
+        "282" => "ALL_MAX_SPEED_AND_OVERTAKING_END",
+        "301" => "RIGHT_OF_WAY",
+        "306" => "PRIORITY",
+        "308" => "PRIORITY_OVER_ONCOMING",
+        "310" => "TOWN_SIGN",
+        "311" => "TOWN_SIGN_BACK",
+        "314" => "PARKING_AREA",
+        "325.1" => "TRAFFIC_CALMED_AREA_START",
+        "325.2" => "TRAFFIC_CALMED_AREA_END",
+        "327" => "TUNNEL",
+        "330.1" => "INTERSTATE_START",
+        "330.2" => "INTERSTATE_END",
+        "331.1" => "HIGHWAY_START",
+        "331.2" => "HIGHWAY_END",
+        "350" => "PEDESTRIANS_CROSSING",
+        "357" => "DEAD_END",
+        "720" => "GREEN_ARROW",
+        _ => {
+            return None;
+        }
+    };
+
+    Some(name)
+}
+
+fn parse_traffic_sign_element(
+    node: Node,
+    country: &str,
+) -> Result<commonroad_pb::TrafficSignElement, XmlError> {
+    use commonroad_pb::traffic_sign_element::TrafficSignElementId;
+    use commonroad_pb::*;
+
+    let sign_id_node = required_child(node, "trafficSignID")?;
+    let sign_id = text(sign_id_node);
+
+    // Signs are either given by their official number or by their name
+    let name = traffic_sign_name(sign_id)
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| screaming_snake_case(sign_id));
+
+    macro_rules! element_id {
+        ($variant:ident, $module:ident :: $enum_name:ident) => {
+            TrafficSignElementId::$variant(
+                $module::$enum_name::from_str_name(&name).unwrap_or($module::$enum_name::Unknown) as i32,
+            )
+        };
+    }
+
+    let element_id = match country {
+        "DEU" => element_id!(GermanyElementId, traffic_sign_id_germany_enum::TrafficSignIdGermany),
+        "USA" => element_id!(UsaElementId, traffic_sign_id_usa_enum::TrafficSignIdUsa),
+        "CHN" => element_id!(ChinaElementId, traffic_sign_id_china_enum::TrafficSignIdChina),
+        "ESP" => element_id!(SpainElementId, traffic_sign_id_spain_enum::TrafficSignIdSpain),
+        "RUS" => element_id!(RussiaElementId, traffic_sign_id_russia_enum::TrafficSignIdRussia),
+        "ARG" => element_id!(ArgentinaElementId, traffic_sign_id_argentina_enum::TrafficSignIdArgentina),
+        "BEL" => element_id!(BelgiumElementId, traffic_sign_id_belgium_enum::TrafficSignIdBelgium),
+        "FRA" => element_id!(FranceElementId, traffic_sign_id_france_enum::TrafficSignIdFrance),
+        "GRC" => element_id!(GreeceElementId, traffic_sign_id_greece_enum::TrafficSignIdGreece),
+        "HRV" => element_id!(CroatiaElementId, traffic_sign_id_croatia_enum::TrafficSignIdCroatia),
+        "ITA" => element_id!(ItalyElementId, traffic_sign_id_italy_enum::TrafficSignIdItaly),
+        "PRI" => element_id!(PuertoRicoElementId, traffic_sign_id_puerto_rico_enum::TrafficSignIdPuertoRico),
+        _ => element_id!(ZamundaElementId, traffic_sign_id_zamunda_enum::TrafficSignIdZamunda),
+    };
+
+    Ok(TrafficSignElement {
+        traffic_sign_element_id: Some(element_id),
+        additional_values: children(node, "additionalValue")
+            .map(|value| text(value).to_owned())
+            .collect(),
+    })
+}
+
+fn parse_traffic_sign(node: Node, country: &str) -> Result<commonroad_pb::TrafficSign, XmlError> {
+    Ok(commonroad_pb::TrafficSign {
+        traffic_sign_id: id(node)?,
+        traffic_sign_elements: children(node, "trafficSignElement")
+            .map(|element| parse_traffic_sign_element(element, country))
+            .collect::<Result<_, _>>()?,
+        first_occurrences: refs(node, "firstOccurrence")?,
+        position: parse_optional_point(node, "position")?,
+        r#virtual: child(node, "virtual").map(parse_bool).transpose()?,
+    })
+}
+
+fn parse_traffic_light(node: Node) -> Result<commonroad_pb::TrafficLight, XmlError> {
+    use commonroad_pb::{
+        traffic_light_direction_enum::TrafficLightDirection, traffic_light_state_enum::TrafficLightState,
+    };
+
+    let cycle = child(node, "cycle");
+
+    let cycle_elements = cycle
+        .into_iter()
+        .flat_map(|cycle| children(cycle, "cycleElement"))
+        .map(|element| {
+            Ok(commonroad_pb::CycleElement {
+                duration: parse_child(element, "duration")?,
+                color: parse_enum(required_child(element, "color")?, TrafficLightState::from_str_name)?,
+            })
+        })
+        .collect::<Result<Vec<_>, XmlError>>()?;
+
+    Ok(commonroad_pb::TrafficLight {
+        traffic_light_id: id(node)?,
+        cycle_elements,
+        position: parse_optional_point(node, "position")?,
+        time_offset: cycle
+            .map(|cycle| parse_optional_child(cycle, "timeOffset"))
+            .transpose()?
+            .flatten(),
+        direction: child(node, "direction")
+            .map(|direction| parse_enum(direction, TrafficLightDirection::from_str_name))
+            .transpose()?,
+        active: child(node, "active").map(parse_bool).transpose()?,
+    })
+}
+
+fn parse_intersection(node: Node) -> Result<commonroad_pb::Intersection, XmlError> {
+    let incomings = children(node, "incoming")
+        .map(|incoming| {
+            Ok(commonroad_pb::Incoming {
+                incoming_id: id(incoming)?,
+                incoming_lanelets: refs(incoming, "incomingLanelet")?,
+                successors_right: refs(incoming, "successorsRight")?,
+                successors_straight: refs(incoming, "successorsStraight")?,
+                successors_left: refs(incoming, "successorsLeft")?,
+                is_left_of: child(incoming, "isLeftOf")
+                    .map(|is_left_of| attribute(is_left_of, "ref"))
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<Vec<_>, XmlError>>()?;
+
+    let crossing_lanelets = match child(node, "crossing") {
+        Some(crossing) => refs(crossing, "crossingLanelet")?,
+        None => vec![],
+    };
+
+    Ok(commonroad_pb::Intersection {
+        intersection_id: id(node)?,
+        incomings,
+        crossing_lanelets,
+    })
+}
+
+fn parse_planning_problem(node: Node) -> Result<commonroad_pb::PlanningProblem, XmlError> {
+    let goal_states = children(node, "goalState")
+        .map(|goal| {
+            let (state, goal_position_lanelets) = parse_state_with_lanelets(goal)?;
+
+            Ok(commonroad_pb::GoalState {
+                state,
+                goal_position_lanelets,
+            })
+        })
+        .collect::<Result<Vec<_>, XmlError>>()?;
+
+    Ok(commonroad_pb::PlanningProblem {
+        planning_problem_id: id(node)?,
+        initial_state: parse_state(required_child(node, "initialState")?)?,
+        goal_states,
+    })
+}
+
+/// Parses dates (`2020-02-03`) and times of day (`10:30`) into a time stamp.
+fn parse_time_stamp(value: &str) -> commonroad_pb::TimeStamp {
+    let mut stamp = commonroad_pb::TimeStamp::default();
+
+    let (date, time) = match value.split_once(['T', ' ']) {
+        Some((date, time)) => (Some(date), Some(time)),
+        None if value.contains(':') => (None, Some(value)),
+        None => (Some(value), None),
+    };
+
+    if let Some(date) = date {
+        let mut parts = date.split('-').map(|part| part.trim().parse().ok());
+        stamp.year = parts.next().flatten();
+        stamp.month = parts.next().flatten();
+        stamp.day = parts.next().flatten();
+    }
+
+    if let Some(time) = time {
+        let mut parts = time.split(':').map(|part| part.trim().parse().ok());
+        stamp.hour = parts.next().flatten();
+        stamp.minute = parts.next().flatten();
+    }
+
+    stamp
+}
+
+fn parse_location(node: Node) -> Result<commonroad_pb::Location, XmlError> {
+    use commonroad_pb::{
+        time_of_day_enum::TimeOfDay, underground_enum::Underground, weather_enum::Weather,
+    };
+
+    let geo_transformation = child(node, "geoTransformation")
+        .map(|geo| {
+            let additional = child(geo, "additionalTransformation");
+            let additional_value = |name| {
+                additional
+                    .map(|additional| parse_optional_child(additional, name))
+                    .transpose()
+                    .map(Option::flatten)
+            };
+
+            Ok::<_, XmlError>(commonroad_pb::GeoTransformation {
+                geo_reference: child(geo, "geoReference").map(|r| text(r).to_owned()),
+                x_translation: additional_value("xTranslation")?,
+                y_translation: additional_value("yTranslation")?,
+                z_rotation: additional_value("zRotation")?,
+                scaling: additional_value("scaling")?,
+            })
+        })
+        .transpose()?;
+
+    let environment = child(node, "environment")
+        .map(|env| {
+            Ok::<_, XmlError>(commonroad_pb::Environment {
+                time: child(env, "time").map(|time| parse_time_stamp(text(time))),
+                time_of_day: child(env, "timeOfDay")
+                    .map(|value| parse_enum(value, TimeOfDay::from_str_name))
+                    .transpose()?,
+                weather: child(env, "weather")
+                    .map(|value| parse_enum(value, Weather::from_str_name))
+                    .transpose()?,
+                underground: child(env, "underground")
+                    .map(|value| parse_enum(value, Underground::from_str_name))
+                    .transpose()?,
+            })
+        })
+        .transpose()?;
+
+    Ok(commonroad_pb::Location {
+        geo_name_id: parse_optional_child(node, "geoNameId")?.unwrap_or_default(),
+        gps_latitude: parse_optional_child(node, "gpsLatitude")?.unwrap_or_default(),
+        gps_longitude: parse_optional_child(node, "gpsLongitude")?.unwrap_or_default(),
+        geo_transformation,
+        environment,
+    })
+}
+
+fn parse_scenario_tags(node: Node) -> Result<commonroad_pb::ScenarioTags, XmlError> {
+    let tags = node
+        .children()
+        .filter(Node::is_element)
+        .map(|tag| {
+            parse_enum_str(tag, tag.tag_name().name(), commonroad_pb::tag_enum::Tag::from_str_name)
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(commonroad_pb::ScenarioTags { tags })
+}
+
+fn parse_information(node: Node) -> Result<commonroad_pb::ScenarioInformation, XmlError> {
+    let text_attribute = |name| node.attribute(name).unwrap_or_default().to_owned();
+
+    Ok(commonroad_pb::ScenarioInformation {
+        common_road_version: text_attribute("commonRoadVersion"),
+        benchmark_id: text_attribute("benchmarkID"),
+        date: parse_time_stamp(node.attribute("date").unwrap_or_default()),
+        author: text_attribute("author"),
+        affiliation: text_attribute("affiliation"),
+        source: text_attribute("source"),
+        time_step_size: attribute(node, "timeStepSize")?,
+    })
+}
+
+pub fn parse_commonroad(input: &str) -> Result<commonroad_pb::CommonRoad, XmlError> {
+    let doc = roxmltree::Document::parse(input)?;
+
+    let root = doc.root_element();
+    if !root.has_tag_name("commonRoad") {
+        return Err(invalid_value(root, root.tag_name().name()));
+    }
+
+    let information = parse_information(root)?;
+
+    // The country code is needed to interpret the traffic sign ids
+    let country = information
+        .benchmark_id
+        .split(['_', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+
+    let mut cr = commonroad_pb::CommonRoad {
+        information,
+        scenario_tags: child(root, "scenarioTags")
+            .map(parse_scenario_tags)
+            .transpose()?
+            .unwrap_or_default(),
+        location: child(root, "location")
+            .map(parse_location)
+            .transpose()?
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+
+    for node in root.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "lanelet" => cr.lanelets.push(parse_lanelet(node)?),
+            "trafficSign" => cr.traffic_signs.push(parse_traffic_sign(node, &country)?),
+            "trafficLight" => cr.traffic_lights.push(parse_traffic_light(node)?),
+            "intersection" => cr.intersections.push(parse_intersection(node)?),
+            "staticObstacle" => cr.static_obstacles.push(parse_static_obstacle(node)?),
+            "dynamicObstacle" => cr.dynamic_obstacles.push(parse_dynamic_obstacle(node)?),
+            "environmentObstacle" => cr.environment_obstacles.push(parse_environment_obstacle(node)?),
+            "phantomObstacle" => cr.phantom_obstacles.push(parse_phantom_obstacle(node)?),
+            "planningProblem" => cr.planning_problems.push(parse_planning_problem(node)?),
+            "location" | "scenarioTags" => {}
+            other => {
+                bevy::log::warn!("ignoring unknown scenario element <{}>", other);
+            }
+        }
+    }
+
+    Ok(cr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use commonroad_pb::*;
+
+    const SCENARIO: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<commonRoad timeStepSize="0.1" commonRoadVersion="2020a" author="Test Author" affiliation="TUM"
+            source="hand written" benchmarkID="DEU_Test-1_1_T-1" date="2020-02-03">
+  <location>
+    <geoNameId>2867714</geoNameId>
+    <gpsLatitude>48.262</gpsLatitude>
+    <gpsLongitude>11.668</gpsLongitude>
+  </location>
+  <scenarioTags>
+    <urban/>
+  </scenarioTags>
+  <lanelet id="1">
+    <leftBound>
+      <point><x>0.0</x><y>3.0</y></point>
+      <point><x>10.0</x><y>3.0</y></point>
+      <lineMarking>solid</lineMarking>
+    </leftBound>
+    <rightBound>
+      <point><x>0.0</x><y>0.0</y></point>
+      <point><x>10.0</x><y>0.0</y></point>
+      <lineMarking>dashed</lineMarking>
+    </rightBound>
+    <laneletType>urban</laneletType>
+    <userOneWay>vehicle</userOneWay>
+    <trafficSignRef ref="10"/>
+    <trafficLightRef ref="20"/>
+  </lanelet>
+  <trafficSign id="10">
+    <trafficSignElement>
+      <trafficSignID>274</trafficSignID>
+      <additionalValue>13.89</additionalValue>
+    </trafficSignElement>
+    <position><point><x>10.0</x><y>-2.0</y></point></position>
+    <firstOccurrence ref="1"/>
+    <virtual>false</virtual>
+  </trafficSign>
+  <trafficLight id="20">
+    <cycle>
+      <cycleElement><duration>30</duration><color>red</color></cycleElement>
+      <cycleElement><duration>20</duration><color>green</color></cycleElement>
+      <timeOffset>5</timeOffset>
+    </cycle>
+    <position><point><x>10.0</x><y>4.0</y></point></position>
+    <direction>all</direction>
+    <active>true</active>
+  </trafficLight>
+  <dynamicObstacle id="30">
+    <type>car</type>
+    <shape>
+      <rectangle><length>4.5</length><width>2.0</width></rectangle>
+    </shape>
+    <initialState>
+      <position><point><x>1.0</x><y>1.5</y></point></position>
+      <orientation><exact>0.0</exact></orientation>
+      <time><exact>0</exact></time>
+      <velocity><exact>10.0</exact></velocity>
+    </initialState>
+    <trajectory>
+      <state>
+        <position><point><x>2.0</x><y>1.5</y></point></position>
+        <orientation><exact>0.0</exact></orientation>
+        <time><exact>1</exact></time>
+        <velocity><exact>10.0</exact></velocity>
+      </state>
+    </trajectory>
+  </dynamicObstacle>
+  <planningProblem id="40">
+    <initialState>
+      <position><point><x>0.5</x><y>1.5</y></point></position>
+      <orientation><exact>0.0</exact></orientation>
+      <time><exact>0</exact></time>
+      <velocity><exact>5.0</exact></velocity>
+      <yawRate><exact>0.0</exact></yawRate>
+      <slipAngle><exact>0.0</exact></slipAngle>
+    </initialState>
+    <goalState>
+      <position><lanelet ref="1"/></position>
+      <time><intervalStart>10</intervalStart><intervalEnd>20</intervalEnd></time>
+    </goalState>
+  </planningProblem>
+</commonRoad>
+"#;
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    fn exact(value: f64) -> Option<FloatExactOrInterval> {
+        Some(FloatExactOrInterval {
+            exact_or_interval: Some(float_exact_or_interval::ExactOrInterval::Exact(value)),
+        })
+    }
+
+    fn exact_time_step(time_step: i32) -> IntegerExactOrInterval {
+        IntegerExactOrInterval {
+            exact_or_interval: Some(integer_exact_or_interval::ExactOrInterval::Exact(time_step)),
+        }
+    }
+
+    fn moving_state(time_step: i32, x: f64, y: f64, velocity: f64) -> State {
+        State {
+            time_step: exact_time_step(time_step),
+            position: Some(state::Position::Point(point(x, y))),
+            orientation: exact(0.0),
+            velocity: exact(velocity),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_minimal_scenario() {
+        let cr = parse_commonroad(SCENARIO).expect("failed to parse scenario");
+
+        assert_eq!(cr.information.benchmark_id, "DEU_Test-1_1_T-1");
+        assert_eq!(cr.information.common_road_version, "2020a");
+        assert_eq!(cr.information.time_step_size, 0.1);
+        assert_eq!(
+            cr.information.date,
+            TimeStamp {
+                year: Some(2020),
+                month: Some(2),
+                day: Some(3),
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(cr.location.geo_name_id, 2867714);
+        assert_eq!(cr.location.gps_latitude, 48.262);
+        assert_eq!(cr.location.gps_longitude, 11.668);
+        assert_eq!(cr.scenario_tags.tags, vec![tag_enum::Tag::Urban as i32]);
+
+        assert_eq!(
+            cr.lanelets,
+            vec![Lanelet {
+                lanelet_id: 1,
+                left_bound: Bound {
+                    points: vec![point(0.0, 3.0), point(10.0, 3.0)],
+                    line_marking: Some(line_marking_enum::LineMarking::Solid as i32),
+                },
+                right_bound: Bound {
+                    points: vec![point(0.0, 0.0), point(10.0, 0.0)],
+                    line_marking: Some(line_marking_enum::LineMarking::Dashed as i32),
+                },
+                predecessors: vec![],
+                successors: vec![],
+                adjacent_left: None,
+                adjacent_right: None,
+                adjacent_left_dir: None,
+                adjacent_right_dir: None,
+                stop_line: None,
+                lanelet_types: vec![lanelet_type_enum::LaneletType::Urban as i32],
+                user_one_way: vec![road_user_enum::RoadUser::Vehicle as i32],
+                user_bidirectional: vec![],
+                traffic_sign_refs: vec![10],
+                traffic_light_refs: vec![20],
+            }]
+        );
+
+        // The sign number is interpreted with the country from the benchmark id
+        assert_eq!(
+            cr.traffic_signs,
+            vec![TrafficSign {
+                traffic_sign_id: 10,
+                traffic_sign_elements: vec![TrafficSignElement {
+                    traffic_sign_element_id: Some(traffic_sign_element::TrafficSignElementId::GermanyElementId(
+                        traffic_sign_id_germany_enum::TrafficSignIdGermany::MaxSpeed as i32,
+                    )),
+                    additional_values: vec!["13.89".to_owned()],
+                }],
+                first_occurrences: vec![1],
+                position: Some(point(10.0, -2.0)),
+                r#virtual: Some(false),
+            }]
+        );
+
+        assert_eq!(
+            cr.traffic_lights,
+            vec![TrafficLight {
+                traffic_light_id: 20,
+                cycle_elements: vec![
+                    CycleElement {
+                        duration: 30,
+                        color: traffic_light_state_enum::TrafficLightState::Red as i32,
+                    },
+                    CycleElement {
+                        duration: 20,
+                        color: traffic_light_state_enum::TrafficLightState::Green as i32,
+                    },
+                ],
+                position: Some(point(10.0, 4.0)),
+                time_offset: Some(5),
+                direction: Some(traffic_light_direction_enum::TrafficLightDirection::All as i32),
+                active: Some(true),
+            }]
+        );
+
+        let shape = Shape {
+            shape: Some(shape::Shape::Rectangle(Rectangle {
+                length: 4.5,
+                width: 2.0,
+                center: None,
+                orientation: None,
+            })),
+        };
+        assert_eq!(
+            cr.dynamic_obstacles,
+            vec![DynamicObstacle {
+                dynamic_obstacle_id: 30,
+                obstacle_type: obstacle_type_enum::ObstacleType::Car as i32,
+                shape: shape.clone(),
+                initial_state: moving_state(0, 1.0, 1.5, 10.0),
+                prediction: Some(dynamic_obstacle::Prediction::TrajectoryPrediction(TrajectoryPrediction {
+                    trajectory: Trajectory {
+                        initial_time_step: 1,
+                        states: vec![moving_state(1, 2.0, 1.5, 10.0)],
+                    },
+                    shape,
+                })),
+                initial_signal_state: None,
+                signal_series: vec![],
+            }]
+        );
+
+        assert_eq!(
+            cr.planning_problems,
+            vec![PlanningProblem {
+                planning_problem_id: 40,
+                initial_state: State {
+                    yaw_rate: exact(0.0),
+                    slip_angle: exact(0.0),
+                    ..moving_state(0, 0.5, 1.5, 5.0)
+                },
+                goal_states: vec![GoalState {
+                    state: State {
+                        time_step: IntegerExactOrInterval {
+                            exact_or_interval: Some(integer_exact_or_interval::ExactOrInterval::Interval(
+                                IntegerInterval { start: 10, end: 20 },
+                            )),
+                        },
+                        ..Default::default()
+                    },
+                    goal_position_lanelets: vec![1],
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_enum_value() {
+        let input = SCENARIO.replace("<type>car</type>", "<type>spaceship</type>");
+
+        let err = parse_commonroad(&input).expect_err("unknown obstacle type was accepted");
+        match &err {
+            XmlError::InvalidValue { element, value, .. } => {
+                assert_eq!(element, "type");
+                assert_eq!(value, "spaceship");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.to_string().contains("<type>"), "{}", err);
+        assert!(err.to_string().contains("'spaceship'"), "{}", err);
+    }
+}
@@ -0,0 +1,358 @@
+use bevy::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use crate::commonroad_pb;
+
+pub struct CoordinatesPlugin;
+
+impl Plugin for CoordinatesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorPosition>()
+            .add_systems(Update, (update_cursor_position, cursor_readout).chain());
+    }
+}
+
+/// Last position of the cursor over the scenario, in local scenario coordinates.
+///
+/// Kept while the cursor is over the UI, so the readout can still be copied.
+#[derive(Resource, Default, PartialEq)]
+pub(crate) struct CursorPosition(Option<Vec2>);
+
+/// WGS84 ellipsoid
+const SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+const FLATTENING: f64 = 1.0 / 298.257_223_563;
+
+/// Transverse Mercator projection on the WGS84 ellipsoid, as used by UTM.
+#[derive(Clone, Debug, PartialEq)]
+struct TransverseMercator {
+    /// Latitude of origin, in radians
+    lat_0: f64,
+    /// Central meridian, in radians
+    lon_0: f64,
+    scale_factor: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+impl TransverseMercator {
+    fn utm(zone: u32, south: bool) -> Self {
+        Self {
+            lat_0: 0.0,
+            lon_0: ((zone as f64 - 1.0) * 6.0 - 180.0 + 3.0).to_radians(),
+            scale_factor: 0.9996,
+            false_easting: 500_000.0,
+            false_northing: if south { 10_000_000.0 } else { 0.0 },
+        }
+    }
+
+    /// Parses the supported subset of PROJ strings, like `+proj=utm +zone=32 +ellps=WGS84`.
+    fn from_proj(proj: &str) -> Option<Self> {
+        let params: Vec<(&str, Option<&str>)> = proj
+            .split_whitespace()
+            .map(|param| {
+                let param = param.trim_start_matches('+');
+                match param.split_once('=') {
+                    Some((key, value)) => (key, Some(value)),
+                    None => (param, None),
+                }
+            })
+            .collect();
+
+        let param = |name: &str| params.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+        let number = |name: &str, default: f64| match param(name) {
+            Some(Some(value)) => value.parse::<f64>().ok(),
+            Some(None) => None,
+            None => Some(default),
+        };
+
+        // Other ellipsoids would need different constants, GRS80 is close enough to WGS84
+        let ellipsoid = param("ellps").flatten().or(param("datum").flatten());
+        if !matches!(ellipsoid, None | Some("WGS84") | Some("GRS80")) {
+            return None;
+        }
+
+        match param("proj")?? {
+            "utm" => {
+                let zone = param("zone")??.parse::<u32>().ok().filter(|zone| (1..=60).contains(zone))?;
+                Some(Self::utm(zone, param("south").is_some()))
+            }
+            "tmerc" => Some(Self {
+                lat_0: number("lat_0", 0.0)?.to_radians(),
+                lon_0: number("lon_0", 0.0)?.to_radians(),
+                scale_factor: number("k_0", number("k", 1.0)?)?,
+                false_easting: number("x_0", 0.0)?,
+                false_northing: number("y_0", 0.0)?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Meridian arc length from the equator to a latitude.
+    fn meridian_arc(lat: f64) -> f64 {
+        let e2 = FLATTENING * (2.0 - FLATTENING);
+        let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+
+        SEMI_MAJOR_AXIS
+            * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * lat
+                - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * lat).sin()
+                + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * lat).sin()
+                - (35.0 * e6 / 3072.0) * (6.0 * lat).sin())
+    }
+
+    /// Inverse projection to latitude and longitude in degrees, following Snyder's series expansion.
+    fn inverse(&self, easting: f64, northing: f64) -> (f64, f64) {
+        let e2 = FLATTENING * (2.0 - FLATTENING);
+        let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+        let ep2 = e2 / (1.0 - e2);
+        let k0 = self.scale_factor;
+
+        let m = Self::meridian_arc(self.lat_0) + (northing - self.false_northing) / k0;
+        let mu = m / (SEMI_MAJOR_AXIS * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+
+        let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+        let footpoint_lat = mu
+            + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+            + (21.0 * e1.powi(2) / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+            + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+            + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+        let (sin, cos, tan) = (footpoint_lat.sin(), footpoint_lat.cos(), footpoint_lat.tan());
+        let c1 = ep2 * cos * cos;
+        let t1 = tan * tan;
+        let n1 = SEMI_MAJOR_AXIS / (1.0 - e2 * sin * sin).sqrt();
+        let r1 = SEMI_MAJOR_AXIS * (1.0 - e2) / (1.0 - e2 * sin * sin).powf(1.5);
+        let d = (easting - self.false_easting) / (n1 * k0);
+
+        let lat = footpoint_lat
+            - (n1 * tan / r1)
+                * (d.powi(2) / 2.0
+                    - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                    + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1) * d.powi(6)
+                        / 720.0);
+        let lon = self.lon_0
+            + (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+                + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1) * d.powi(5) / 120.0)
+                / cos;
+
+        (lat.to_degrees(), lon.to_degrees())
+    }
+}
+
+/// Conversion from local scenario coordinates to WGS84, from the geo transformation of a scenario.
+///
+/// Follows the `geoTransformation` of the CommonRoad 2020a XML format documentation:
+/// the `geoReference` PROJ string gives the projection of WGS84 coordinates, and the
+/// `additionalTransformation` then translates, rotates around the z axis and scales the projected coordinates.
+#[derive(Clone, Debug, PartialEq)]
+struct GeoReference {
+    projection: TransverseMercator,
+    translation: (f64, f64),
+    rotation: f64,
+    scaling: f64,
+}
+
+impl GeoReference {
+    fn new(transformation: &commonroad_pb::GeoTransformation) -> Result<Self, String> {
+        let proj = transformation.geo_reference.as_deref().unwrap_or_default();
+        let projection =
+            TransverseMercator::from_proj(proj).ok_or_else(|| format!("unsupported geo reference \"{}\"", proj))?;
+
+        let scaling = transformation.scaling.unwrap_or(1.0);
+        if scaling == 0.0 {
+            return Err("geo transformation has a scaling of zero".to_owned());
+        }
+
+        Ok(Self {
+            projection,
+            translation: (
+                transformation.x_translation.unwrap_or(0.0),
+                transformation.y_translation.unwrap_or(0.0),
+            ),
+            rotation: transformation.z_rotation.unwrap_or(0.0),
+            scaling,
+        })
+    }
+
+    /// Latitude and longitude in degrees of a local position.
+    ///
+    /// The scenario coordinates are the projected coordinates translated, then rotated and scaled,
+    /// so this undoes the additional transformation before the inverse projection.
+    fn to_wgs84(&self, local: Vec2) -> (f64, f64) {
+        let (x, y) = (local.x as f64 / self.scaling, local.y as f64 / self.scaling);
+        let (sin, cos) = (-self.rotation).sin_cos();
+
+        let easting = x * cos - y * sin - self.translation.0;
+        let northing = x * sin + y * cos - self.translation.1;
+
+        self.projection.inverse(easting, northing)
+    }
+}
+
+fn geo_reference(cr: &commonroad_pb::CommonRoad) -> Result<GeoReference, String> {
+    match &cr.location.geo_transformation {
+        Some(transformation) if transformation.geo_reference.is_some() => GeoReference::new(transformation),
+        _ => Err("no geo reference".to_owned()),
+    }
+}
+
+fn update_cursor_position(
+    mut contexts: EguiContexts,
+    window_q: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<crate::MainCamera>>,
+    mut cursor: ResMut<CursorPosition>,
+) {
+    if contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+
+    let Ok(window) = window_q.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_q.get_single() else {
+        return;
+    };
+
+    let position = window
+        .cursor_position()
+        .and_then(|pos| camera.viewport_to_world_2d(camera_transform, pos));
+    if let Some(position) = position {
+        cursor.set_if_neq(CursorPosition(Some(position)));
+    }
+}
+
+fn cursor_readout(
+    mut contexts: EguiContexts,
+    cr: Res<crate::CommonRoad>,
+    cursor: Res<CursorPosition>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut geo: Local<Option<Result<GeoReference, String>>>,
+) {
+    if cr.is_changed() {
+        *geo = None;
+    }
+
+    let Some(position) = cursor.0 else {
+        return;
+    };
+    let local = format!("{:.2}, {:.2}", position.x, position.y);
+    let wgs84 = match geo.get_or_insert_with(|| geo_reference(&cr)) {
+        Ok(geo) => {
+            let (lat, lon) = geo.to_wgs84(position);
+            Ok(format!("{:.7}, {:.7}", lat, lon))
+        }
+        Err(err) => Err(err.as_str()),
+    };
+
+    let ctx = contexts.ctx_mut();
+
+    // Keyboard input is already cleared while egui is focused
+    let mut copy = keys.just_pressed(KeyCode::KeyC);
+
+    egui::Window::new("Cursor")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::Vec2::new(8.0, -8.0))
+        .show(ctx, |ui| {
+            egui::Grid::new("cursor coordinates").num_columns(2).show(ui, |ui| {
+                ui.label("x, y");
+                ui.monospace(format!("{} m", local));
+                ui.end_row();
+
+                ui.label("lat, lon");
+                match &wgs84 {
+                    Ok(wgs84) => ui.monospace(wgs84),
+                    Err(err) => ui.weak(*err),
+                };
+                ui.end_row();
+            });
+
+            copy |= ui
+                .small_button("\u{1F4CB} Copy coordinates")
+                .on_hover_text("Copies the last cursor position over the scenario, also with the C key")
+                .clicked();
+        });
+
+    if copy {
+        // One line per row of the readout
+        let text = match wgs84 {
+            Ok(wgs84) => format!("{}\n{}", local, wgs84),
+            Err(_) => local,
+        };
+        ctx.output_mut(|output| output.copied_text = text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Largest difference to the expected latitude and longitude, in degrees (about 0.1 m)
+    const TOLERANCE: f64 = 1e-6;
+
+    /// Easting and northing of 55° N, 12° E in UTM zone 32N, from the PROJ documentation
+    /// (`echo 12 55 | proj +proj=utm +zone=32 +ellps=WGS84`)
+    const UTM_32N_POINT: (f64, f64) = (691_875.63, 6_098_907.83);
+
+    fn assert_close((lat, lon): (f64, f64), (expected_lat, expected_lon): (f64, f64)) {
+        assert!(
+            (lat - expected_lat).abs() < TOLERANCE && (lon - expected_lon).abs() < TOLERANCE,
+            "got {}, {}, expected {}, {}",
+            lat,
+            lon,
+            expected_lat,
+            expected_lon,
+        );
+    }
+
+    #[test]
+    fn inverse_utm_projection() {
+        let projection = TransverseMercator::from_proj("+proj=utm +zone=32 +ellps=WGS84 +units=m +no_defs").unwrap();
+        assert_eq!(projection, TransverseMercator::utm(32, false));
+
+        let (easting, northing) = UTM_32N_POINT;
+        assert_close(projection.inverse(easting, northing), (55.0, 12.0));
+
+        // The central meridian at the equator
+        assert_close(projection.inverse(500_000.0, 0.0), (0.0, 9.0));
+    }
+
+    #[test]
+    fn rejects_unsupported_geo_references() {
+        assert_eq!(TransverseMercator::from_proj("+proj=longlat +datum=WGS84"), None);
+        assert_eq!(TransverseMercator::from_proj("+proj=utm +zone=61"), None);
+        assert_eq!(TransverseMercator::from_proj("+proj=utm +zone=32 +ellps=bessel"), None);
+    }
+
+    #[test]
+    fn transformed_scenario_position_to_wgs84() {
+        let (translation, rotation, scaling) = ((-691_000.0, -6_098_000.0), 0.4_f64, 1.5);
+
+        let cr = commonroad_pb::CommonRoad {
+            location: commonroad_pb::Location {
+                gps_latitude: 55.0,
+                gps_longitude: 12.0,
+                geo_transformation: Some(commonroad_pb::GeoTransformation {
+                    geo_reference: Some("+proj=utm +zone=32 +ellps=WGS84".to_owned()),
+                    x_translation: Some(translation.0),
+                    y_translation: Some(translation.1),
+                    z_rotation: Some(rotation),
+                    scaling: Some(scaling),
+                }),
+                ..default()
+            },
+            ..default()
+        };
+
+        // Position of the GPS coordinates in the scenario: translated, then rotated, then scaled
+        let (x, y) = (UTM_32N_POINT.0 + translation.0, UTM_32N_POINT.1 + translation.1);
+        let (sin, cos) = rotation.sin_cos();
+        let local = Vec2::new(
+            ((x * cos - y * sin) * scaling) as f32,
+            ((x * sin + y * cos) * scaling) as f32,
+        );
+
+        let geo = geo_reference(&cr).unwrap();
+        assert_close(geo.to_wgs84(local), (cr.location.gps_latitude, cr.location.gps_longitude));
+    }
+}
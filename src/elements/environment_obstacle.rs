@@ -0,0 +1,55 @@
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb::{self, obstacle_type_enum::ObstacleType};
+
+use super::shape::ShapeOutline;
+
+/// Above the lanelets, but below the static and dynamic obstacles
+const ENVIRONMENT_OBSTACLE_Z: f32 = 2.0;
+
+/// Fill and stroke colors of an environment obstacle.
+fn obstacle_style(obstacle_type: ObstacleType) -> (Color, Color) {
+    match obstacle_type {
+        ObstacleType::Building => (Color::srgb_u8(181, 164, 140), Color::srgb_u8(120, 105, 85)),
+        ObstacleType::Pillar => (Color::srgb_u8(90, 90, 95), Color::srgb_u8(40, 40, 45)),
+        ObstacleType::MedianStrip => (Color::srgb_u8(120, 160, 90), Color::srgb_u8(70, 110, 50)),
+        _ => (Color::srgb_u8(150, 150, 150), Color::srgb_u8(80, 80, 80)),
+    }
+}
+
+fn spawn_environment_obstacle(commands: &mut Commands, obs: &commonroad_pb::EnvironmentObstacle) {
+    let (fill, stroke) = obstacle_style(obs.obstacle_type());
+
+    commands.spawn((
+        Name::new(format!("environment obstacle {}", obs.environment_obstacle_id)),
+        super::SceneEntity,
+        ShapeBundle {
+            // The shape is given in absolute coordinates
+            path: GeometryBuilder::build_as(&ShapeOutline(&obs.obstacle_shape)),
+            spatial: SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, ENVIRONMENT_OBSTACLE_Z),
+                ..default()
+            },
+            ..default()
+        },
+        Fill::color(fill),
+        Stroke::new(stroke, 0.1),
+        super::HoverTooltip::bundle(format!(
+            "{:?} {}",
+            obs.obstacle_type(),
+            obs.environment_obstacle_id
+        )),
+        RaycastPickable,
+    ));
+}
+
+pub fn spawn_environment_obstacles(mut commands: Commands, cr: Res<crate::CommonRoad>) {
+    for obs in &cr.environment_obstacles {
+        spawn_environment_obstacle(&mut commands, obs);
+    }
+}
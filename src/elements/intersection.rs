@@ -0,0 +1,357 @@
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb;
+use crate::global_settings::GlobalSettings;
+
+/// Above the traffic signs, but below the occupancies
+const INCOMING_MARKER_Z: f32 = 2.7;
+
+/// Above the lanelet centerlines, but below the goal regions
+const HIGHLIGHT_Z: f32 = 1.3;
+
+/// Entity only shown while the intersection overlay is enabled.
+#[derive(Component)]
+pub struct IntersectionOverlay;
+
+#[derive(Component)]
+pub struct IncomingMarker {
+    intersection_id: u32,
+    incoming_id: u32,
+}
+
+/// Highlighted lanelet of the selected incoming.
+#[derive(Component)]
+pub struct IncomingHighlight;
+
+/// Incoming whose successors are highlighted, as intersection and incoming id.
+#[derive(Resource, Default)]
+pub(crate) struct SelectedIncoming(Option<(u32, u32)>);
+
+#[derive(Event)]
+pub(crate) struct SelectIncomingEvent(Entity);
+
+impl From<bevy_eventlistener::callbacks::ListenerInput<Pointer<Click>>> for SelectIncomingEvent {
+    fn from(value: bevy_eventlistener::callbacks::ListenerInput<Pointer<Click>>) -> Self {
+        Self(value.listener())
+    }
+}
+
+/// Role of a lanelet for the selected incoming.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum IncomingRole {
+    Incoming,
+    Left,
+    Straight,
+    Right,
+    Crossing,
+}
+
+impl IncomingRole {
+    const ALL: [IncomingRole; 5] = [
+        IncomingRole::Incoming,
+        IncomingRole::Left,
+        IncomingRole::Straight,
+        IncomingRole::Right,
+        IncomingRole::Crossing,
+    ];
+
+    fn color(self) -> [u8; 3] {
+        match self {
+            IncomingRole::Incoming => [70, 130, 180],
+            IncomingRole::Left => [255, 140, 0],
+            IncomingRole::Straight => [60, 179, 113],
+            IncomingRole::Right => [147, 112, 219],
+            IncomingRole::Crossing => [220, 20, 60],
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IncomingRole::Incoming => "incoming lanelets",
+            IncomingRole::Left => "left successors",
+            IncomingRole::Straight => "straight successors",
+            IncomingRole::Right => "right successors",
+            IncomingRole::Crossing => "crossing lanelets",
+        }
+    }
+
+    fn lanelets(self, intersection: &commonroad_pb::Intersection, incoming: &commonroad_pb::Incoming) -> Vec<u32> {
+        match self {
+            IncomingRole::Incoming => incoming.incoming_lanelets.clone(),
+            IncomingRole::Left => incoming.successors_left.clone(),
+            IncomingRole::Straight => incoming.successors_straight.clone(),
+            IncomingRole::Right => incoming.successors_right.clone(),
+            IncomingRole::Crossing => intersection.crossing_lanelets.clone(),
+        }
+    }
+}
+
+fn find_lanelet(cr: &commonroad_pb::CommonRoad, id: u32) -> Option<&commonroad_pb::Lanelet> {
+    let lanelet = cr.lanelets.iter().find(|lanelet| lanelet.lanelet_id == id);
+    if lanelet.is_none() {
+        bevy::log::warn!("intersection refers to unknown lanelet {}", id);
+    }
+
+    lanelet
+}
+
+/// Position of an incoming, at the average end of its lanelets.
+fn incoming_position(cr: &commonroad_pb::CommonRoad, incoming: &commonroad_pb::Incoming) -> Option<Vec2> {
+    let ends: Vec<Vec2> = incoming
+        .incoming_lanelets
+        .iter()
+        .filter_map(|id| find_lanelet(cr, *id))
+        .filter_map(super::lanelet::lanelet_end)
+        .collect();
+
+    if ends.is_empty() {
+        return None;
+    }
+
+    Some(ends.iter().sum::<Vec2>() / ends.len() as f32)
+}
+
+fn spawn_intersection(commands: &mut Commands, cr: &commonroad_pb::CommonRoad, intersection: &commonroad_pb::Intersection) {
+    let intersection_id = intersection.intersection_id;
+
+    let group = commands
+        .spawn((
+            Name::new(format!("intersection {}", intersection_id)),
+            super::SceneEntity,
+            IntersectionOverlay,
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .id();
+
+    let positions: Vec<Option<Vec2>> = intersection
+        .incomings
+        .iter()
+        .map(|incoming| incoming_position(cr, incoming))
+        .collect();
+
+    for (incoming, position) in std::iter::zip(&intersection.incomings, &positions) {
+        let Some(position) = position else {
+            bevy::log::warn!(
+                "incoming {} of intersection {} has no lanelets",
+                incoming.incoming_id,
+                intersection_id
+            );
+            continue;
+        };
+
+        commands
+            .spawn((
+                Name::new(format!("incoming {}", incoming.incoming_id)),
+                IncomingMarker {
+                    intersection_id,
+                    incoming_id: incoming.incoming_id,
+                },
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&shapes::Circle {
+                        radius: 1.2,
+                        center: Vec2::ZERO,
+                    }),
+                    spatial: SpatialBundle {
+                        transform: Transform::from_translation(position.extend(INCOMING_MARKER_Z)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Fill::color(bevy::color::palettes::css::DARK_CYAN),
+                Stroke::new(Color::WHITE, 0.2),
+                super::HoverTooltip::bundle(format!(
+                    "Incoming {} of intersection {}",
+                    incoming.incoming_id, intersection_id
+                )),
+                RaycastPickable,
+                On::<Pointer<Click>>::send_event::<SelectIncomingEvent>(),
+            ))
+            .set_parent(group);
+
+        // Right-of-way relation, pointing to the incoming this one is left of
+        let Some(left_of) = incoming.is_left_of else {
+            continue;
+        };
+        let target = std::iter::zip(&intersection.incomings, &positions)
+            .find(|(other, _)| other.incoming_id == left_of)
+            .and_then(|(_, position)| *position);
+        let Some(target) = target else {
+            bevy::log::warn!("incoming {} is left of unknown incoming {}", incoming.incoming_id, left_of);
+            continue;
+        };
+
+        // Keep the arrow clear of both markers
+        let direction = (target - *position).normalize_or_zero() * 1.8;
+
+        commands
+            .spawn((
+                Name::new(format!("incoming {} is left of {}", incoming.incoming_id, left_of)),
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&crate::extra_shapes::Arrow {
+                        from: *position + direction,
+                        to: target - direction,
+                        head_length: 1.5,
+                    }),
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, INCOMING_MARKER_Z),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Stroke::new(bevy::color::palettes::css::DARK_CYAN, 0.25),
+                super::HoverTooltip::bundle(format!(
+                    "Incoming {} is left of incoming {}",
+                    incoming.incoming_id, left_of
+                )),
+                RaycastPickable,
+            ))
+            .set_parent(group);
+    }
+}
+
+pub fn spawn_intersections(mut commands: Commands, cr: Res<crate::CommonRoad>) {
+    for intersection in &cr.intersections {
+        spawn_intersection(&mut commands, &cr, intersection);
+    }
+}
+
+pub(crate) fn intersection_visibility(
+    settings: Res<GlobalSettings>,
+    mut overlay_q: Query<(Ref<IntersectionOverlay>, &mut Visibility)>,
+) {
+    let visibility = if settings.show_intersections {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    for (overlay, mut overlay_visibility) in overlay_q.iter_mut() {
+        if settings.is_changed() || overlay.is_added() {
+            overlay_visibility.set_if_neq(visibility);
+        }
+    }
+}
+
+pub(crate) fn select_clicked_incomings(
+    mut events: EventReader<SelectIncomingEvent>,
+    marker_q: Query<&IncomingMarker>,
+    mut selected: ResMut<SelectedIncoming>,
+) {
+    for SelectIncomingEvent(entity) in events.read() {
+        let Ok(marker) = marker_q.get(*entity) else {
+            continue;
+        };
+
+        let incoming = (marker.intersection_id, marker.incoming_id);
+        // Clicking the selected incoming again clears the selection
+        selected.0 = if selected.0 == Some(incoming) { None } else { Some(incoming) };
+    }
+}
+
+/// Spawns the highlighted lanelets of the selected incoming.
+pub(crate) fn update_incoming_highlight(
+    mut commands: Commands,
+    selected: Res<SelectedIncoming>,
+    settings: Res<GlobalSettings>,
+    cr: Res<crate::CommonRoad>,
+
+    highlight_q: Query<Entity, With<IncomingHighlight>>,
+) {
+    if !selected.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for entity in highlight_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some((intersection_id, incoming_id)) = selected.0 else {
+        return;
+    };
+    if !settings.show_intersections {
+        return;
+    }
+
+    let Some(intersection) = cr.intersections.iter().find(|i| i.intersection_id == intersection_id) else {
+        return;
+    };
+    let Some(incoming) = intersection.incomings.iter().find(|i| i.incoming_id == incoming_id) else {
+        return;
+    };
+
+    for (idx, role) in IncomingRole::ALL.into_iter().enumerate() {
+        let [r, g, b] = role.color();
+        let color = Color::srgb_u8(r, g, b);
+
+        for id in role.lanelets(intersection, incoming) {
+            let Some(lanelet) = find_lanelet(&cr, id) else {
+                continue;
+            };
+
+            commands.spawn((
+                Name::new(format!("{} {}", role.label(), id)),
+                IncomingHighlight,
+                super::SceneEntity,
+                ShapeBundle {
+                    path: GeometryBuilder::build_as(&shapes::Polygon {
+                        points: super::lanelet::lanelet_outline(lanelet),
+                        closed: true,
+                    }),
+                    spatial: SpatialBundle {
+                        // Lanelets with several roles show the first one
+                        transform: Transform::from_xyz(0.0, 0.0, HIGHLIGHT_Z - idx as f32 * 1e-3),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Fill::color(color.with_alpha(0.45)),
+                Stroke::new(color, 0.15),
+            ));
+        }
+    }
+}
+
+pub(crate) fn incoming_window(
+    mut contexts: EguiContexts,
+    mut selected: ResMut<SelectedIncoming>,
+    settings: Res<GlobalSettings>,
+) {
+    let Some((intersection_id, incoming_id)) = selected.0 else {
+        return;
+    };
+    if !settings.show_intersections {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+
+    let mut open = true;
+    egui::Window::new("Intersection")
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.heading(format!("Incoming {} of intersection {}", incoming_id, intersection_id));
+
+            for role in IncomingRole::ALL {
+                let [r, g, b] = role.color();
+
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("\u{25A0}").color(egui::Color32::from_rgb(r, g, b)));
+                    ui.label(role.label());
+                });
+            }
+        });
+
+    if !open {
+        selected.0 = None;
+    }
+}
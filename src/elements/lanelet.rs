@@ -1,8 +1,14 @@
+use backends::raycast::RaycastPickable;
 use bevy::prelude::*;
 
+use bevy_mod_picking::prelude::*;
+
+use bevy_egui::EguiContexts;
+
 use bevy_prototype_lyon::prelude::*;
 
-use crate::commonroad_pb;
+use crate::commonroad_pb::{self, lanelet_type_enum::LaneletType, road_user_enum::RoadUser};
+use crate::global_settings::{GlobalSettings, LaneletColoring};
 
 #[derive(Component)]
 pub struct LeftBound;
@@ -16,6 +22,179 @@ pub struct LaneletBackground;
 #[derive(Component)]
 pub struct Lanelet;
 
+#[derive(Component)]
+pub struct Centerline;
+
+/// Spacing of the direction chevrons along the centerline, in meters
+const CHEVRON_SPACING: f32 = 8.0;
+
+/// Half the width and length of a direction chevron, in meters
+const CHEVRON_SIZE: f32 = 0.6;
+
+/// Number of samples along each bound to compute the centerline from
+const CENTERLINE_SAMPLES: usize = 64;
+
+#[derive(Component)]
+pub struct LaneletData(commonroad_pb::Lanelet);
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct HoveredLanelet;
+
+/// Lanelet shown in the inspector window.
+#[derive(Resource, Default)]
+pub(crate) struct InspectedLanelet(Option<u32>);
+
+#[derive(Event)]
+pub(crate) struct InspectLaneletEvent(Entity);
+
+impl From<bevy_eventlistener::callbacks::ListenerInput<Pointer<Click>>> for InspectLaneletEvent {
+    fn from(value: bevy_eventlistener::callbacks::ListenerInput<Pointer<Click>>) -> Self {
+        Self(value.listener())
+    }
+}
+
+/// Relation of a lanelet to the hovered or inspected one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Neighbour {
+    Focus,
+    Predecessor,
+    Successor,
+    AdjacentLeft,
+    AdjacentRight,
+}
+
+impl Neighbour {
+    const ALL: [Neighbour; 5] = [
+        Neighbour::Focus,
+        Neighbour::Predecessor,
+        Neighbour::Successor,
+        Neighbour::AdjacentLeft,
+        Neighbour::AdjacentRight,
+    ];
+
+    fn of(focus: &commonroad_pb::Lanelet, id: u32) -> Option<Self> {
+        if focus.lanelet_id == id {
+            Some(Neighbour::Focus)
+        } else if focus.predecessors.contains(&id) {
+            Some(Neighbour::Predecessor)
+        } else if focus.successors.contains(&id) {
+            Some(Neighbour::Successor)
+        } else if focus.adjacent_left == Some(id) {
+            Some(Neighbour::AdjacentLeft)
+        } else if focus.adjacent_right == Some(id) {
+            Some(Neighbour::AdjacentRight)
+        } else {
+            None
+        }
+    }
+
+    fn color(self) -> Color {
+        use bevy::color::palettes::css;
+
+        match self {
+            Neighbour::Focus => css::STEEL_BLUE.into(),
+            Neighbour::Predecessor => css::MEDIUM_PURPLE.into(),
+            Neighbour::Successor => css::MEDIUM_SEA_GREEN.into(),
+            Neighbour::AdjacentLeft => css::DARK_ORANGE.into(),
+            Neighbour::AdjacentRight => css::GOLDENROD.into(),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Neighbour::Focus => "lanelet",
+            Neighbour::Predecessor => "predecessors",
+            Neighbour::Successor => "successors",
+            Neighbour::AdjacentLeft => "left neighbour",
+            Neighbour::AdjacentRight => "right neighbour",
+        }
+    }
+}
+
+/// Colour of lanelets without any highlighting, the same as CSS gray
+const PLAIN_COLOR: [u8; 3] = [128, 128, 128];
+
+fn lanelet_background_color() -> Color {
+    let [r, g, b] = PLAIN_COLOR;
+
+    Color::srgb_u8(r, g, b)
+}
+
+/// Lanelet types with their own colour, from the most to the least specific.
+const LANELET_TYPE_COLORS: [(LaneletType, [u8; 3]); 12] = [
+    (LaneletType::Crosswalk, [235, 235, 235]),
+    (LaneletType::Sidewalk, [190, 175, 150]),
+    (LaneletType::BicycleLane, [170, 90, 80]),
+    (LaneletType::BusStop, [200, 160, 40]),
+    (LaneletType::BusLane, [160, 120, 40]),
+    (LaneletType::Parking, [90, 110, 160]),
+    (LaneletType::Shoulder, [110, 110, 100]),
+    (LaneletType::Restricted, [120, 60, 60]),
+    (LaneletType::Border, [70, 70, 70]),
+    (LaneletType::Intersection, [150, 130, 160]),
+    (LaneletType::AccessRamp, [110, 140, 120]),
+    (LaneletType::ExitRamp, [110, 140, 120]),
+];
+
+const EGO_ALLOWED_COLOR: [u8; 3] = [110, 150, 110];
+const EGO_FORBIDDEN_COLOR: [u8; 3] = [160, 90, 90];
+
+fn lanelet_type_color(lanelet: &commonroad_pb::Lanelet) -> Option<[u8; 3]> {
+    let types: Vec<LaneletType> = lanelet.lanelet_types().collect();
+
+    LANELET_TYPE_COLORS
+        .iter()
+        .find(|(lanelet_type, _)| types.contains(lanelet_type))
+        .map(|(_, color)| *color)
+}
+
+/// Whether the ego vehicle class may drive on a lanelet, `None` if the lanelet has no user restrictions.
+fn ego_allowed(lanelet: &commonroad_pb::Lanelet, ego: RoadUser) -> Option<bool> {
+    let mut users = lanelet.user_one_way().chain(lanelet.user_bidirectional()).peekable();
+    users.peek()?;
+
+    let is_vehicle = !matches!(ego, RoadUser::Bicycle | RoadUser::Pedestrian | RoadUser::Train);
+
+    Some(users.any(|user| user == ego || (user == RoadUser::Vehicle && is_vehicle)))
+}
+
+fn lanelet_color(lanelet: &commonroad_pb::Lanelet, settings: &GlobalSettings) -> Color {
+    let color = match settings.lanelet_coloring {
+        LaneletColoring::Plain => None,
+        LaneletColoring::Type => lanelet_type_color(lanelet),
+        LaneletColoring::EgoAllowed => ego_allowed(lanelet, settings.ego_road_user)
+            .map(|allowed| if allowed { EGO_ALLOWED_COLOR } else { EGO_FORBIDDEN_COLOR }),
+    };
+
+    color.map_or_else(lanelet_background_color, |[r, g, b]| Color::srgb_u8(r, g, b))
+}
+
+/// Legend of the current lanelet colour scheme.
+pub(crate) fn lanelet_color_legend(ui: &mut egui::Ui, settings: &GlobalSettings) {
+    let entry = |ui: &mut egui::Ui, [r, g, b]: [u8; 3], label: &str| {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("\u{25A0}").color(egui::Color32::from_rgb(r, g, b)));
+            ui.label(label);
+        });
+    };
+
+    match settings.lanelet_coloring {
+        LaneletColoring::Plain => {}
+        LaneletColoring::Type => {
+            for (lanelet_type, color) in LANELET_TYPE_COLORS {
+                entry(ui, color, &format!("{:?}", lanelet_type));
+            }
+            entry(ui, PLAIN_COLOR, "other");
+        }
+        LaneletColoring::EgoAllowed => {
+            entry(ui, EGO_ALLOWED_COLOR, "allowed");
+            entry(ui, EGO_FORBIDDEN_COLOR, "not allowed");
+            entry(ui, PLAIN_COLOR, "no restrictions given");
+        }
+    }
+}
+
 fn make_dashed(path: &lyon_path::Path, dash_length: f32, dash_ratio: f32) -> lyon_path::Path {
     use lyon_algorithms::measure::{PathMeasurements, SampleType};
 
@@ -39,6 +218,85 @@ fn make_dashed(path: &lyon_path::Path, dash_length: f32, dash_ratio: f32) -> lyo
     builder.build()
 }
 
+fn polyline_path(points: impl Iterator<Item = Vec2>) -> lyon_path::Path {
+    let polyline = crate::extra_shapes::Polyline {
+        points: points.collect(),
+    };
+
+    GeometryBuilder::build_as(&polyline).0
+}
+
+/// Centerline of a lanelet, halfway between both bounds sampled at the same relative distance.
+fn make_centerline(lanelet: &commonroad_pb::Lanelet) -> Vec<Vec2> {
+    use lyon_algorithms::measure::{PathMeasurements, SampleType};
+
+    let left_path = polyline_path(lanelet.left_bound.points.iter().map(Into::into));
+    let right_path = polyline_path(lanelet.right_bound.points.iter().map(Into::into));
+
+    let left_measurements = PathMeasurements::from_path(&left_path, 1e-3);
+    let right_measurements = PathMeasurements::from_path(&right_path, 1e-3);
+    let mut left_sampler = left_measurements.create_sampler(&left_path, SampleType::Normalized);
+    let mut right_sampler = right_measurements.create_sampler(&right_path, SampleType::Normalized);
+
+    (0..=CENTERLINE_SAMPLES)
+        .map(|i| {
+            let t = i as f32 / CENTERLINE_SAMPLES as f32;
+            let left = left_sampler.sample(t).position();
+            let right = right_sampler.sample(t).position();
+
+            Vec2::new(left.x + right.x, left.y + right.y) / 2.0
+        })
+        .collect()
+}
+
+/// Polyline with chevrons pointing along it every `spacing` meters.
+struct DirectedPolyline {
+    points: Vec<Vec2>,
+    spacing: f32,
+    size: f32,
+}
+
+impl Geometry for DirectedPolyline {
+    fn add_geometry(&self, b: &mut lyon_path::path::Builder) {
+        use lyon_algorithms::measure::{PathMeasurements, SampleType};
+
+        let path = polyline_path(self.points.iter().copied());
+
+        let measurements = PathMeasurements::from_path(&path, 1e-3);
+        let mut sampler = measurements.create_sampler(&path, SampleType::Distance);
+        let length = sampler.length();
+        let chevron_count = (length / self.spacing).floor() as i32;
+
+        // Lanelets shorter than the spacing still get a single chevron in their middle
+        let distances: Vec<f32> = if chevron_count == 0 && length > 0.0 {
+            vec![length / 2.0]
+        } else {
+            (0..chevron_count).map(|i| (i as f32 + 0.5) * self.spacing).collect()
+        };
+
+        let mut points = self.points.iter().map(|p| lyon_path::math::point(p.x, p.y));
+        if let Some(first) = points.next() {
+            b.begin(first);
+            for point in points {
+                b.line_to(point);
+            }
+            b.end(false);
+        }
+
+        for distance in distances {
+            let sample = sampler.sample(distance);
+            let tip = sample.position();
+            let tangent = sample.tangent().normalize() * self.size;
+            let normal = lyon_path::math::vector(-tangent.y, tangent.x);
+
+            b.begin(tip - tangent + normal);
+            b.line_to(tip);
+            b.line_to(tip - tangent - normal);
+            b.end(false);
+        }
+    }
+}
+
 fn spawn_bound(bound: &commonroad_pb::Bound, z_idx: f32) -> Option<impl Bundle> {
     let bound_pts = bound
         .points
@@ -119,6 +377,24 @@ fn spawn_bound(bound: &commonroad_pb::Bound, z_idx: f32) -> Option<impl Bundle>
     ))
 }
 
+/// Outline of a lanelet, along the left bound and back along the right bound.
+pub(crate) fn lanelet_outline(lanelet: &commonroad_pb::Lanelet) -> Vec<Vec2> {
+    let lbound_pts = lanelet.left_bound.points.iter().map(Into::<Vec2>::into);
+    let rbound_pts = lanelet.right_bound.points.iter().map(Into::<Vec2>::into);
+
+    lbound_pts.chain(rbound_pts.rev()).collect()
+}
+
+/// End of a lanelet, between the last points of both bounds.
+pub(crate) fn lanelet_end(lanelet: &commonroad_pb::Lanelet) -> Option<Vec2> {
+    lanelet
+        .left_bound
+        .points
+        .last()
+        .zip(lanelet.right_bound.points.last())
+        .map(|(l, r)| (Vec2::from(l) + Vec2::from(r)) / 2.0)
+}
+
 pub fn spawn_lanelet(commands: &mut Commands, lanelet: &commonroad_pb::Lanelet, z_idx: f32) {
     let _span =
         bevy::log::info_span!("spawning lanelet", lanelet_id = lanelet.lanelet_id).entered();
@@ -130,6 +406,7 @@ pub fn spawn_lanelet(commands: &mut Commands, lanelet: &commonroad_pb::Lanelet,
         };
         commands.spawn((
             Name::new("stop line"),
+            super::SceneEntity,
             ShapeBundle {
                 path: GeometryBuilder::build_as(&stop_line_shape),
                 spatial: SpatialBundle {
@@ -142,15 +419,8 @@ pub fn spawn_lanelet(commands: &mut Commands, lanelet: &commonroad_pb::Lanelet,
         ));
     };
 
-    let lbound_pts = lanelet.left_bound.points.iter().map(Into::<Vec2>::into);
-    let rbound_pts = lanelet.right_bound.points.iter().map(Into::<Vec2>::into);
-
-    let mut fpoints: Vec<Vec2> = vec![];
-    fpoints.extend(lbound_pts.clone());
-    fpoints.extend(rbound_pts.clone().rev());
-
     let ll_shape: shapes::Polygon = bevy_prototype_lyon::shapes::Polygon {
-        points: fpoints,
+        points: lanelet_outline(lanelet),
         closed: false,
     };
 
@@ -158,6 +428,7 @@ pub fn spawn_lanelet(commands: &mut Commands, lanelet: &commonroad_pb::Lanelet,
         .spawn((
             Name::new(format!("lanelet {}", lanelet.lanelet_id)),
             Lanelet,
+            super::SceneEntity,
             SpatialBundle::default(),
         ))
         .id();
@@ -174,7 +445,13 @@ pub fn spawn_lanelet(commands: &mut Commands, lanelet: &commonroad_pb::Lanelet,
                 },
                 ..default()
             },
-            Fill::color(bevy::color::palettes::css::GRAY),
+            Fill::color(lanelet_background_color()),
+            LaneletData(lanelet.to_owned()),
+            PickableBundle::default(),
+            RaycastPickable,
+            On::<Pointer<Over>>::target_insert(HoveredLanelet),
+            On::<Pointer<Out>>::target_remove::<HoveredLanelet>(),
+            On::<Pointer<Click>>::send_event::<InspectLaneletEvent>(),
         ))
         .set_parent_in_place(main_entity);
 
@@ -188,6 +465,30 @@ pub fn spawn_lanelet(commands: &mut Commands, lanelet: &commonroad_pb::Lanelet,
             .spawn((Name::new("right bound"), RightBound, bound))
             .set_parent(main_entity);
     }
+
+    let centerline = DirectedPolyline {
+        points: make_centerline(lanelet),
+        spacing: CHEVRON_SPACING,
+        size: CHEVRON_SIZE,
+    };
+
+    commands
+        .spawn((
+            Name::new("centerline"),
+            Centerline,
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&centerline),
+                spatial: SpatialBundle {
+                    // Above the lanelet backgrounds
+                    transform: Transform::from_xyz(0.0, 0.0, 1.2),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ..default()
+            },
+            Stroke::new(Color::srgba(1.0, 1.0, 1.0, 0.6), 0.08),
+        ))
+        .set_parent(main_entity);
     /*
     commands.spawn((
         LeftBound,
@@ -218,3 +519,203 @@ pub fn spawn_lanelets(mut commands: Commands, cr: Res<crate::CommonRoad>) {
         spawn_lanelet(&mut commands, lanelet, z_idx);
     }
 }
+
+pub(crate) fn inspect_clicked_lanelets(
+    mut events: EventReader<InspectLaneletEvent>,
+    lanelet_q: Query<&LaneletData>,
+    mut inspected: ResMut<InspectedLanelet>,
+) {
+    for InspectLaneletEvent(entity) in events.read() {
+        let Ok(LaneletData(lanelet)) = lanelet_q.get(*entity) else {
+            continue;
+        };
+
+        // Clicking the inspected lanelet again closes the inspector
+        inspected.0 = if inspected.0 == Some(lanelet.lanelet_id) {
+            None
+        } else {
+            Some(lanelet.lanelet_id)
+        };
+    }
+}
+
+/// Colours the lanelets by the selected scheme,
+/// and the neighbours of the hovered lanelet, or else of the inspected one.
+pub(crate) fn update_lanelet_colors(
+    settings: Res<GlobalSettings>,
+    inspected: Res<InspectedLanelet>,
+    hovered_q: Query<&LaneletData, With<HoveredLanelet>>,
+    mut lanelet_q: Query<(&LaneletData, &mut Fill)>,
+    added_q: Query<(), Added<LaneletData>>,
+    mut last_focus: Local<Option<u32>>,
+) {
+    let focus_id = hovered_q
+        .iter()
+        .next()
+        .map(|LaneletData(lanelet)| lanelet.lanelet_id)
+        .or(inspected.0);
+
+    if focus_id == *last_focus && added_q.is_empty() && !settings.is_changed() {
+        return;
+    }
+    *last_focus = focus_id;
+
+    let focus = focus_id.and_then(|id| {
+        lanelet_q
+            .iter()
+            .find(|(LaneletData(lanelet), _)| lanelet.lanelet_id == id)
+            .map(|(LaneletData(lanelet), _)| lanelet.clone())
+    });
+
+    for (LaneletData(lanelet), mut fill) in lanelet_q.iter_mut() {
+        let neighbour = focus.as_ref().and_then(|focus| Neighbour::of(focus, lanelet.lanelet_id));
+
+        fill.color = neighbour.map_or_else(|| lanelet_color(lanelet, &settings), Neighbour::color);
+    }
+}
+
+fn road_users(users: impl Iterator<Item = commonroad_pb::road_user_enum::RoadUser>) -> String {
+    let users: Vec<_> = users.map(|user| format!("{:?}", user)).collect();
+
+    if users.is_empty() {
+        "-".to_owned()
+    } else {
+        users.join(", ")
+    }
+}
+
+fn neighbour_color(neighbour: Neighbour) -> egui::Color32 {
+    let color = neighbour.color().to_srgba();
+    let to_u8 = |v: f32| (v * 255.0).round() as u8;
+
+    egui::Color32::from_rgb(to_u8(color.red), to_u8(color.green), to_u8(color.blue))
+}
+
+/// Attributes and topology of a lanelet, returns a clicked neighbour.
+fn lanelet_details(ui: &mut egui::Ui, lanelet: &commonroad_pb::Lanelet) -> Option<u32> {
+    let mut clicked = None;
+
+    let types: Vec<_> = lanelet.lanelet_types().map(|t| format!("{:?}", t)).collect();
+    ui.label(format!("types: {}", types.join(", ")));
+    ui.label(format!("one way for: {}", road_users(lanelet.user_one_way())));
+    ui.label(format!("bidirectional for: {}", road_users(lanelet.user_bidirectional())));
+
+    ui.separator();
+
+    let mut neighbour_row = |ui: &mut egui::Ui, neighbour: Neighbour, ids: &[u32], suffix: Option<String>| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new(format!("{}:", neighbour.label())).color(neighbour_color(neighbour)));
+
+            if ids.is_empty() {
+                ui.label("-");
+            }
+            for id in ids {
+                if ui.small_button(id.to_string()).clicked() {
+                    clicked = Some(*id);
+                }
+            }
+            if let Some(suffix) = suffix {
+                ui.label(suffix);
+            }
+        });
+    };
+
+    neighbour_row(ui, Neighbour::Predecessor, &lanelet.predecessors, None);
+    neighbour_row(ui, Neighbour::Successor, &lanelet.successors, None);
+    neighbour_row(
+        ui,
+        Neighbour::AdjacentLeft,
+        lanelet.adjacent_left.as_slice(),
+        lanelet.adjacent_left.map(|_| format!("({:?} direction)", lanelet.adjacent_left_dir())),
+    );
+    neighbour_row(
+        ui,
+        Neighbour::AdjacentRight,
+        lanelet.adjacent_right.as_slice(),
+        lanelet.adjacent_right.map(|_| format!("({:?} direction)", lanelet.adjacent_right_dir())),
+    );
+
+    clicked
+}
+
+pub(crate) fn lanelet_tooltip(
+    mut contexts: EguiContexts,
+    inspected: Res<InspectedLanelet>,
+
+    lanelet_q: Query<&LaneletData, With<HoveredLanelet>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    let base_id = egui::Id::new("lanelet tooltip");
+    let layer_id = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("lanelet tooltips"));
+
+    for LaneletData(lanelet) in lanelet_q.iter() {
+        // The inspector already shows everything
+        if inspected.0 == Some(lanelet.lanelet_id) {
+            continue;
+        }
+
+        egui::containers::show_tooltip(ctx, layer_id, base_id.with(lanelet.lanelet_id), |ui| {
+            ui.heading(format!("Lanelet {}", lanelet.lanelet_id));
+            lanelet_details(ui, lanelet);
+        });
+    }
+}
+
+pub(crate) fn lanelet_inspector(
+    mut contexts: EguiContexts,
+    mut inspected: ResMut<InspectedLanelet>,
+    cr: Res<crate::CommonRoad>,
+) {
+    let Some(id) = inspected.0 else {
+        return;
+    };
+    let Some(lanelet) = cr.lanelets.iter().find(|l| l.lanelet_id == id) else {
+        inspected.0 = None;
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+
+    let mut open = true;
+    let mut clicked = None;
+
+    egui::Window::new("Lanelet Inspector")
+        .open(&mut open)
+        .default_width(300.0)
+        .show(ctx, |ui| {
+            ui.heading(format!("Lanelet {}", lanelet.lanelet_id));
+            clicked = lanelet_details(ui, lanelet);
+
+            ui.separator();
+            ui.horizontal_wrapped(|ui| {
+                for neighbour in Neighbour::ALL {
+                    ui.label(egui::RichText::new("\u{25A0}").color(neighbour_color(neighbour)));
+                    ui.label(neighbour.label());
+                }
+            });
+        });
+
+    if !open {
+        inspected.0 = None;
+    } else if clicked.is_some() {
+        inspected.0 = clicked;
+    }
+}
+
+pub(crate) fn centerline_visibility(
+    settings: Res<crate::global_settings::GlobalSettings>,
+    mut centerline_q: Query<(Ref<Centerline>, &mut Visibility)>,
+) {
+    let visibility = if settings.show_centerlines {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    for (centerline, mut centerline_visibility) in centerline_q.iter_mut() {
+        if settings.is_changed() || centerline.is_added() {
+            centerline_visibility.set_if_neq(visibility);
+        }
+    }
+}
@@ -1,8 +1,19 @@
 use bevy::prelude::*;
 
+use crate::loading::AppState;
+
+pub(crate) mod environment_obstacle;
+pub(crate) mod intersection;
 pub(crate) mod lanelet;
 pub(crate) mod obstacle;
+pub(crate) mod occupancy;
+pub(crate) mod planning_problem;
 pub(crate) mod ref_path;
+pub(crate) mod shape;
+pub(crate) mod signal;
+pub(crate) mod static_obstacle;
+pub(crate) mod traffic_light;
+pub(crate) mod traffic_sign;
 pub(crate) mod trajectory;
 
 pub struct ElementsPlugin;
@@ -12,34 +23,101 @@ impl Plugin for ElementsPlugin {
         app
             .init_resource::<trajectory::TrajectorySortKey>()
             .init_resource::<trajectory::SortDirection>()
-            .add_systems(Startup,
+            .init_resource::<lanelet::InspectedLanelet>()
+            .add_event::<lanelet::InspectLaneletEvent>()
+            .init_resource::<intersection::SelectedIncoming>()
+            .add_event::<intersection::SelectIncomingEvent>()
+            .init_resource::<obstacle::SelectedObstacle>()
+            .add_event::<obstacle::SelectObstacleEvent>()
+            .add_systems(OnEnter(AppState::Unloaded), despawn_scene)
+            .add_systems(OnEnter(AppState::Loading),
                 (
-                    obstacle::spawn_obstacles.after(crate::camera_setup),
+                    obstacle::spawn_obstacles,
+                    static_obstacle::spawn_static_obstacles,
+                    environment_obstacle::spawn_environment_obstacles,
                     lanelet::spawn_lanelets,
-                    trajectory::spawn_trajectories,
+                    trajectory::start_loading_logs,
                     ref_path::spawn_ref_path,
+                    planning_problem::spawn_planning_problems,
+                    traffic_light::spawn_traffic_lights,
+                    traffic_sign::spawn_traffic_signs,
+                    intersection::spawn_intersections,
                 )
             )
             .add_systems(Update,
                 (
-                        trajectory::update_stroke,
+                        trajectory::update_stroke.run_if(resource_exists::<trajectory::MaxCosts>),
                         trajectory::trajectory_group_visibility,
+                        trajectory::finish_loading_logs.run_if(resource_exists::<trajectory::LogsLoadingTask>),
+                        trajectory::watch::reload_changed_logs.run_if(resource_exists::<trajectory::watch::LogsWatcher>),
+                        trajectory::watch::finish_reloading_main_log
+                            .run_if(resource_exists::<trajectory::watch::MainLogLoadingTask>),
+                        trajectory::live::poll_live_logs
+                            .run_if(resource_exists::<trajectory::live::LiveLogs>)
+                            .run_if(not(resource_exists::<trajectory::LogsLoadingTask>)),
+                        trajectory::watch::restore_selection
+                            .run_if(resource_exists::<trajectory::watch::RestoreSelection>)
+                            .before(trajectory::cache::spawn_loaded_trajectory_groups),
+                        (
+                            trajectory::cache::request_trajectory_groups,
+                            trajectory::cache::spawn_loaded_trajectory_groups,
+                        ).run_if(resource_exists::<trajectory::cache::TrajectoryCache>),
                         trajectory::trajectory_visibility,
                         trajectory::trajectory_tooltip,
                         obstacle::obstacle_tooltip,
+                        static_obstacle::static_obstacle_tooltip,
                         obstacle::trajectory_animation,
+                        signal::signal_lights,
+                        occupancy::occupancy_visibility,
                         ref_path::ref_path_tooltip,
-                        // obstacle::plot_obs,
                         show_generic_tooltips,
                 )
             )
+            .add_systems(Update,
+                (
+                    planning_problem::update_goal_status
+                        .run_if(resource_exists_and_changed::<trajectory::MainTrajectory>),
+                    planning_problem::goal_tooltip,
+                    planning_problem::planning_problem_window,
+                    traffic_light::traffic_light_colors,
+                    traffic_light::traffic_light_tooltip,
+                    traffic_sign::traffic_sign_labels,
+                    traffic_sign::traffic_sign_tooltip,
+                )
+            )
+            .add_systems(Update,
+                (
+                    lanelet::inspect_clicked_lanelets,
+                    lanelet::update_lanelet_colors,
+                    lanelet::lanelet_tooltip,
+                    lanelet::lanelet_inspector,
+                ).chain()
+            )
+            .add_systems(Update, lanelet::centerline_visibility)
+            .add_systems(Update,
+                (
+                    obstacle::select_clicked_obstacles,
+                    obstacle::obstacle_details
+                        .run_if(resource_exists::<crate::global_settings::CurrentTimeStep>),
+                ).chain()
+            )
+            .add_systems(Update,
+                (
+                    intersection::intersection_visibility,
+                    intersection::select_clicked_incomings,
+                    intersection::update_incoming_highlight,
+                    intersection::incoming_window,
+                ).chain()
+            )
             .add_systems(Update,
                 (
                     (
                         trajectory::trajectory_list,
                     ),
                     (
-                        trajectory::trajectory_window,
+                        trajectory::trajectory_window
+                            .run_if(resource_exists::<trajectory::MainTrajectory>)
+                            .run_if(resource_exists::<trajectory::VehicleParams>),
                         trajectory::sort_trajectory_list,
                     ),
                 ).chain()
@@ -49,7 +127,10 @@ impl Plugin for ElementsPlugin {
                 fix_render_asset_usages.after(bevy_prototype_lyon::plugin::BuildShapes)
             )
             .add_event::<trajectory::SelectTrajectoryEvent>()
-            .add_systems(PostUpdate, trajectory::update_selected_trajectory);
+            .add_systems(
+                PostUpdate,
+                trajectory::update_selected_trajectory.run_if(resource_exists::<trajectory::MaxCosts>),
+            );
 
         app.register_type::<trajectory::TrajectoryLog>()
             .register_type::<trajectory::MainLog>()
@@ -57,6 +138,45 @@ impl Plugin for ElementsPlugin {
     }
 }
 
+/// Top-level entity spawned from the opened run, despawned again when another run is opened.
+#[derive(Clone, Copy, Component, Reflect)]
+pub(crate) struct SceneEntity;
+
+/// Removes everything spawned from the previous run and starts loading the current one.
+fn despawn_scene(
+    mut commands: Commands,
+    scene_q: Query<Entity, With<SceneEntity>>,
+    cts: Option<ResMut<crate::global_settings::CurrentTimeStep>>,
+    mut ts: ResMut<crate::global_settings::TimeStep>,
+    mut diagnostics: ResMut<crate::loading::LoadDiagnostics>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for entity in scene_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    trajectory::remove_logs(&mut commands);
+    commands.remove_resource::<trajectory::watch::LogsWatcher>();
+    commands.remove_resource::<trajectory::watch::RestoreSelection>();
+    commands.remove_resource::<trajectory::live::LiveLogs>();
+    commands.remove_resource::<planning_problem::GoalStatus>();
+    commands.remove_resource::<traffic_sign::SpeedLimits>();
+    commands.insert_resource(lanelet::InspectedLanelet::default());
+    commands.insert_resource(intersection::SelectedIncoming::default());
+    commands.insert_resource(obstacle::SelectedObstacle::default());
+
+    // The time step from the arguments is set once the obstacles are spawned, within their prediction range
+    if let Some(mut cts) = cts {
+        cts.dynamic_time_step = 0.0;
+        cts.prediction_range = 0.0..=0.0;
+    }
+    ts.time_step = 0;
+
+    diagnostics.clear();
+
+    next_state.set(AppState::Loading);
+}
+
 fn fix_render_asset_usages(
     mut meshes: ResMut<Assets<Mesh>>,
     query: Query<
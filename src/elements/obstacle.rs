@@ -1,3 +1,4 @@
+use backends::raycast::RaycastPickable;
 use bevy::prelude::*;
 
 use bevy_mod_picking::prelude::*;
@@ -8,10 +9,56 @@ use bevy_prototype_lyon::prelude::*;
 
 use crate::commonroad_pb::{self, DynamicObstacle};
 
-use crate::commonroad_pb::{integer_exact_or_interval, CommonRoad};
+use crate::commonroad_pb::integer_exact_or_interval;
+use crate::global_settings::CurrentTimeStep;
 use egui_plot::PlotPoints;
 
-fn state_transform(state: &commonroad_pb::State) -> Option<Transform> {
+/// Width of each state plot in the obstacle details panel
+const STATE_PLOT_WIDTH: f32 = 320.0;
+
+type StateField = fn(&commonroad_pb::State) -> &Option<commonroad_pb::FloatExactOrInterval>;
+
+/// State fields that are plotted over time if an obstacle trajectory contains them, with their units.
+const STATE_FIELDS: &[(&str, &str, StateField)] = &[
+    ("velocity", "m/s", |st| &st.velocity),
+    ("acceleration", "m/s\u{b2}", |st| &st.acceleration),
+    ("jerk", "m/s\u{b3}", |st| &st.jerk),
+    ("orientation", "rad", |st| &st.orientation),
+    ("yaw rate", "rad/s", |st| &st.yaw_rate),
+    ("steering angle", "rad", |st| &st.steering_angle),
+    ("steering angle speed", "rad/s", |st| &st.steering_angle_speed),
+    ("slip angle", "rad", |st| &st.slip_angle),
+    ("curvature", "1/m", |st| &st.curvature),
+    ("curvature rate", "1/(m s)", |st| &st.curvature_rate),
+    ("lateral velocity", "m/s", |st| &st.velocity_y),
+    ("lateral acceleration", "m/s\u{b2}", |st| &st.acceleration_y),
+    ("roll angle", "rad", |st| &st.roll_angle),
+    ("roll rate", "rad/s", |st| &st.roll_rate),
+    ("pitch angle", "rad", |st| &st.pitch_angle),
+    ("pitch rate", "rad/s", |st| &st.pitch_rate),
+    ("z position", "m", |st| &st.position_z),
+    ("z velocity", "m/s", |st| &st.velocity_z),
+    ("front roll angle", "rad", |st| &st.roll_angle_front),
+    ("front roll rate", "rad/s", |st| &st.roll_rate_front),
+    ("front lateral velocity", "m/s", |st| &st.velocity_y_front),
+    ("front z position", "m", |st| &st.position_z_front),
+    ("front z velocity", "m/s", |st| &st.velocity_z_front),
+    ("rear roll angle", "rad", |st| &st.roll_angle_rear),
+    ("rear roll rate", "rad/s", |st| &st.roll_rate_rear),
+    ("rear lateral velocity", "m/s", |st| &st.velocity_y_rear),
+    ("rear z position", "m", |st| &st.position_z_rear),
+    ("rear z velocity", "m/s", |st| &st.velocity_z_rear),
+    ("front wheel angular speed", "rad/s", |st| &st.front_wheel_angular_speed),
+    ("rear wheel angular speed", "rad/s", |st| &st.rear_wheel_angular_speed),
+    ("left front wheel angular speed", "rad/s", |st| &st.left_front_wheel_angular_speed),
+    ("right front wheel angular speed", "rad/s", |st| &st.right_front_wheel_angular_speed),
+    ("left rear wheel angular speed", "rad/s", |st| &st.left_rear_wheel_angular_speed),
+    ("right rear wheel angular speed", "rad/s", |st| &st.right_rear_wheel_angular_speed),
+    ("front lateral deviation", "m", |st| &st.delta_y_f),
+    ("rear lateral deviation", "m", |st| &st.delta_y_r),
+];
+
+pub(super) fn state_transform(state: &commonroad_pb::State) -> Option<Transform> {
     let position: Vec2 = match state.position.as_ref()? {
         commonroad_pb::state::Position::Point(p) => Vec2::from(p.clone()),
         _ => {
@@ -31,14 +78,28 @@ fn state_transform(state: &commonroad_pb::State) -> Option<Transform> {
 }
 
 #[derive(Component)]
-pub struct ObstacleData(commonroad_pb::DynamicObstacle);
+pub struct ObstacleData(pub(crate) commonroad_pb::DynamicObstacle);
 
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 pub struct HoveredObstacle;
 
+/// Dynamic obstacle shown in the details panel.
+#[derive(Resource, Default)]
+pub(crate) struct SelectedObstacle(Option<u32>);
+
+#[derive(Event)]
+pub(crate) struct SelectObstacleEvent(Entity);
+
+impl From<bevy_eventlistener::callbacks::ListenerInput<Pointer<Click>>> for SelectObstacleEvent {
+    fn from(value: bevy_eventlistener::callbacks::ListenerInput<Pointer<Click>>) -> Self {
+        Self(value.listener())
+    }
+}
+
 pub fn obstacle_tooltip(
     mut contexts: EguiContexts,
+    ts: Res<crate::global_settings::TimeStep>,
 
     obstacle_q: Query<&ObstacleData, With<HoveredObstacle>>,
 ) {
@@ -55,11 +116,15 @@ pub fn obstacle_tooltip(
                 obs.obstacle_type()
             ));
 
-            ui.label(format!("signal series: {:#?}", obs.signal_series));
-            ui.label(format!(
-                "initial signal state: {:#?}",
-                obs.initial_signal_state
-            ));
+            if !super::signal::has_signals(obs.initial_signal_state.as_ref(), &obs.signal_series) {
+                ui.label("no signal states");
+                return;
+            }
+
+            let state =
+                super::signal::signal_state_at(obs.initial_signal_state.as_ref(), &obs.signal_series, ts.time_step);
+            super::signal::signal_grid(ui, ("obstacle signals", obs.dynamic_obstacle_id), &state, ts.time_step);
+            ui.label(format!("signal series: {} states", obs.signal_series.len()));
         });
     }
 }
@@ -94,38 +159,37 @@ pub fn spawn_obstacle(
     commands: &mut Commands,
     obs: &commonroad_pb::DynamicObstacle,
 ) -> Option<i32> {
-    let shape = match obs.shape.shape.as_ref().unwrap() {
-        commonroad_pb::shape::Shape::Rectangle(r) => bevy_prototype_lyon::shapes::Rectangle {
-            extents: Vec2::new(r.length as f32, r.width as f32),
-            origin: RectangleOrigin::Center,
-        },
-        _ => unimplemented!(),
-    };
-    let rect_path: bevy_prototype_lyon::prelude::Path = GeometryBuilder::build_as(&shape);
+    let obstacle_path = GeometryBuilder::build_as(&super::shape::ShapeOutline(&obs.shape));
     let simple_marker = bevy_prototype_lyon::shapes::Circle {
         radius: 0.2 * 1e3,
         center: Vec2::ZERO,
     };
 
+    let mut transform = state_transform(&obs.initial_state).unwrap_or_else(|| {
+        bevy::log::warn!(
+            "obstacle {} has no exact initial position and orientation",
+            obs.dynamic_obstacle_id
+        );
+        Transform::IDENTITY
+    });
+    transform.translation.z = 4.0;
+
     let main_entity = commands
         .spawn((
             Name::new(format!("obstacle group {}", obs.dynamic_obstacle_id)),
+            super::SceneEntity,
             SpatialBundle::default(),
         ))
         .id();
 
-    let _obstacle_entity = commands
+    let obstacle_entity = commands
         .spawn((
             Name::new("obstacle"),
             ObstacleData(obs.to_owned()),
             ShapeBundle {
-                path: rect_path,
+                path: obstacle_path,
                 spatial: SpatialBundle {
-                    transform: {
-                        let mut t = state_transform(&obs.initial_state).unwrap();
-                        t.translation.z = 4.0;
-                        t
-                    },
+                    transform,
                     ..default()
                 },
                 ..default()
@@ -133,10 +197,8 @@ pub fn spawn_obstacle(
             Fill::color(Color::WHITE),
             Stroke::new(bevy::color::palettes::css::ORANGE, 0.2),
             PickableBundle::default(),
-            // RaycastPickTarget::default(),
-            On::<Pointer<Down>>::target_commands_mut(|_click, _commands| {
-                bevy::log::info!("clicked obstacle!");
-            }),
+            RaycastPickable,
+            On::<Pointer<Click>>::send_event::<SelectObstacleEvent>(),
             On::<Pointer<Over>>::target_commands_mut(|_click, commands| {
                 commands.insert(HoveredObstacle);
             }),
@@ -144,25 +206,38 @@ pub fn spawn_obstacle(
                 commands.remove::<HoveredObstacle>();
             }),
         ))
-        .set_parent_in_place(main_entity);
+        .set_parent_in_place(main_entity)
+        .id();
 
-    let Some(commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj)) =
-        &obs.prediction
-    else {
-        return None;
+    super::signal::spawn_signal_lights(commands, obstacle_entity, obs);
+
+    let traj = match &obs.prediction {
+        Some(commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj)) => traj,
+        Some(commonroad_pb::dynamic_obstacle::Prediction::SetBasedPrediction(prediction)) => {
+            let label = format!("Obstacle {}", obs.dynamic_obstacle_id);
+            let color = Color::srgba_u8(255, 165, 0, 110);
+
+            return super::occupancy::spawn_occupancies(commands, main_entity, &label, prediction, color);
+        }
+        None => {
+            return None;
+        }
     };
 
     let max_ts = traj
         .trajectory
         .states
         .iter()
-        .map(|s: &commonroad_pb::State| s.time_step().unwrap())
+        .filter_map(|s: &commonroad_pb::State| s.time_step())
         .max();
 
     for st in &traj.trajectory.states {
-        let time_step = match st.time_step.exact_or_interval {
-            Some(integer_exact_or_interval::ExactOrInterval::Exact(i)) => i,
-            _ => unimplemented!(),
+        let (Some(time_step), Some(transform)) = (st.time_step(), state_transform(st)) else {
+            bevy::log::warn!(
+                "skipping a trajectory state of obstacle {} without an exact time step, position and orientation",
+                obs.dynamic_obstacle_id
+            );
+            continue;
         };
         let ts_color = Color::srgba_u8(
             130_u8.saturating_sub((time_step as u8).saturating_mul(2)),
@@ -177,8 +252,7 @@ pub fn spawn_obstacle(
                 ShapeBundle {
                     path: GeometryBuilder::build_as(&simple_marker),
                     spatial: SpatialBundle {
-                        transform: state_transform(st)
-                            .unwrap()
+                        transform: transform
                             .mul_transform(Transform::from_xyz(
                                 0.0,
                                 0.0,
@@ -197,50 +271,55 @@ pub fn spawn_obstacle(
     return max_ts;
 }
 
-fn velocity_points(obs: &commonroad_pb::DynamicObstacle) -> Option<PlotPoints> {
-    let commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj) =
-        &obs.prediction.as_ref()?
-    else {
-        return None;
-    };
+/// Initial state followed by the states of the trajectory prediction.
+fn trajectory_states(obs: &commonroad_pb::DynamicObstacle) -> Option<Vec<&commonroad_pb::State>> {
+    match obs.prediction.as_ref()? {
+        commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj) => {
+            Some(std::iter::once(&obs.initial_state).chain(&traj.trajectory.states).collect())
+        }
+        commonroad_pb::dynamic_obstacle::Prediction::SetBasedPrediction(_) => None,
+    }
+}
 
-    let velocity_pts = traj
-        .trajectory
-        .states
+/// Exact values of a state field over the time steps, skipping states without one.
+fn state_points(states: &[&commonroad_pb::State], field: StateField) -> Vec<[f64; 2]> {
+    states
         .iter()
-        .map(|st| {
-            let v = st.velocity.clone()?.try_into().ok()?;
+        .filter_map(|st| {
+            let value: f64 = field(st).clone()?.try_into().ok()?;
             let ts: i32 = st.time_step.clone().try_into().ok()?;
-            Some([ts as f64, v])
+            Some([ts as f64, value])
         })
-        .collect::<Option<Vec<_>>>()?;
-
-    Some(PlotPoints::new(velocity_pts))
+        .collect()
 }
 
-fn numerical_velocity_points(obs: &commonroad_pb::DynamicObstacle) -> Option<PlotPoints> {
-    let commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj) =
-        &obs.prediction.as_ref()?
-    else {
-        return None;
-    };
+/// Positions of the trajectory states, as x and y over the time steps.
+fn position_points(states: &[&commonroad_pb::State]) -> (Vec<[f64; 2]>, Vec<[f64; 2]>) {
+    states
+        .iter()
+        .filter_map(|st| {
+            let pos: Vec2 = st.position.clone()?.try_into().ok()?;
+            let ts: i32 = st.time_step.clone().try_into().ok()?;
+            Some(([ts as f64, pos.x as f64], [ts as f64, pos.y as f64]))
+        })
+        .unzip()
+}
 
-    let velocity_pts = traj
-        .trajectory
-        .states
-        .as_slice()
+/// Velocity from the distance to the neighbouring states, to cross-check the given velocities.
+fn numerical_velocity_points(states: &[&commonroad_pb::State], time_step_size: f64) -> Option<PlotPoints> {
+    let velocity_pts = states
         .windows(3)
         .map(|states| {
-            let prev = &states[0];
-            let this = &states[1];
-            let next = &states[2];
+            let prev = states[0];
+            let this = states[1];
+            let next = states[2];
             let p_prev: Vec2 = prev.position.clone()?.try_into().ok()?;
             let p_this: Vec2 = this.position.clone()?.try_into().ok()?;
             let p_next: Vec2 = next.position.clone()?.try_into().ok()?;
 
             let v1 = p_this.distance(p_prev);
             let v2 = p_this.distance(p_next);
-            let v = (v1 + v2) as f64 / 2.0;
+            let v = (v1 + v2) as f64 / 2.0 / time_step_size;
 
             let ts: i32 = this.time_step.clone().try_into().ok()?;
             Some([ts as f64, v])
@@ -269,54 +348,167 @@ fn lerp_states(states: &Vec<commonroad_pb::State>, s: f32) -> Option<Transform>
 }
 
 pub fn trajectory_animation(
-    mut obstacle_q: Query<(&ObstacleData, &mut Transform)>,
+    mut obstacle_q: Query<(&ObstacleData, &mut Transform, &mut Visibility)>,
     cts: Res<crate::global_settings::CurrentTimeStep>,
 ) {
     if !cts.is_changed() {
         return;
     }
 
-    for (obs, mut transform) in obstacle_q.iter_mut() {
-        let commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj) =
-            &obs.0.prediction.as_ref().unwrap()
-        else {
-            return;
+    for (obs, mut transform, mut visibility) in obstacle_q.iter_mut() {
+        let traj = match &obs.0.prediction {
+            Some(commonroad_pb::dynamic_obstacle::Prediction::TrajectoryPrediction(traj)) => traj,
+            Some(commonroad_pb::dynamic_obstacle::Prediction::SetBasedPrediction(_)) => {
+                // Later positions are only known from the occupancies
+                let initial = obs.0.initial_state.time_step().unwrap_or(0);
+                let visible = cts.dynamic_time_step.round() as i32 == initial;
+
+                visibility.set_if_neq(if visible { Visibility::Inherited } else { Visibility::Hidden });
+                continue;
+            }
+            None => {
+                continue;
+            }
         };
         let states = &traj.trajectory.states;
 
-        *transform = lerp_states(states, cts.dynamic_time_step).unwrap();
+        if let Some(new_transform) = lerp_states(states, cts.dynamic_time_step) {
+            *transform = new_transform;
+        }
+    }
+}
+
+pub(crate) fn select_clicked_obstacles(
+    mut events: EventReader<SelectObstacleEvent>,
+    obstacle_q: Query<&ObstacleData>,
+    mut selected: ResMut<SelectedObstacle>,
+) {
+    for SelectObstacleEvent(entity) in events.read() {
+        let Ok(ObstacleData(obs)) = obstacle_q.get(*entity) else {
+            continue;
+        };
+
+        // Clicking the selected obstacle again closes the details
+        selected.0 = if selected.0 == Some(obs.dynamic_obstacle_id) {
+            None
+        } else {
+            Some(obs.dynamic_obstacle_id)
+        };
     }
 }
 
-#[allow(unused)]
-pub fn plot_obs(mut contexts: EguiContexts, cr: Res<CommonRoad>) {
+/// Plots all state fields of the selected obstacle over time,
+/// clicking a plot moves to the time step under the cursor.
+pub(crate) fn obstacle_details(
+    mut contexts: EguiContexts,
+    cr: Res<crate::CommonRoad>,
+    mut selected: ResMut<SelectedObstacle>,
+    mut cts: ResMut<CurrentTimeStep>,
+) {
+    let Some(id) = selected.0 else {
+        return;
+    };
+    let Some(obs) = cr.dynamic_obstacles.iter().find(|obs| obs.dynamic_obstacle_id == id) else {
+        selected.0 = None;
+        return;
+    };
+
     let ctx = contexts.ctx_mut();
 
-    egui::Window::new("Obstacle Velocity").show(ctx, |ui| {
-        egui_plot::Plot::new("velocity_plot")
-            // .clamp_grid(true)
-            .legend(egui_plot::Legend::default())
-            .view_aspect(2.0)
-            .min_size(egui::Vec2::new(400.0, 200.0))
-            .sharp_grid_lines(true)
-            .show(ui, |pui| {
-                for obs in &cr.dynamic_obstacles {
-                    let pp = velocity_points(obs).unwrap();
-                    let line = egui_plot::Line::new(pp)
-                        .name(format!("velocity [m/s] for {}", obs.dynamic_obstacle_id));
-                    pui.line(line);
-
-                    let npp = numerical_velocity_points(obs).unwrap();
-                    let nline = egui_plot::Line::new(npp).name(format!(
-                        "numerical velocity [m/s] for {}",
-                        obs.dynamic_obstacle_id
-                    ));
-                    pui.line(nline);
-
-                    // pui.set_plot_bounds(PlotBounds::from_min_max([1.0, 0.0], [40.0, 20.0]));
+    let mut open = true;
+    let mut clicked_time_step = None;
+
+    egui::TopBottomPanel::bottom("obstacle details")
+        .resizable(true)
+        .default_height(260.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(format!("Obstacle {} (type {:?})", id, obs.obstacle_type()));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("\u{2716}").on_hover_text("Close").clicked() {
+                        open = false;
+                    }
+                });
+            });
+
+            let Some(states) = trajectory_states(obs) else {
+                ui.label("The obstacle has no trajectory prediction, its occupancies are shown in the scenario.");
+                return;
+            };
+
+            let group = egui::Id::new("obstacle plot group");
+            let ts_vline = egui_plot::VLine::new(cts.dynamic_time_step)
+                .style(egui_plot::LineStyle::Dotted { spacing: 0.1 });
+
+            let mut plots: Vec<(&str, &str, Vec<egui_plot::Line>)> = vec![];
+
+            let (x, y) = position_points(&states);
+            if !x.is_empty() {
+                plots.push((
+                    "position",
+                    "m",
+                    vec![
+                        egui_plot::Line::new(PlotPoints::new(x)).name("x"),
+                        egui_plot::Line::new(PlotPoints::new(y)).name("y"),
+                    ],
+                ));
+            }
+
+            for &(name, unit, field) in STATE_FIELDS {
+                let points = state_points(&states, field);
+                if points.is_empty() {
+                    continue;
+                }
+
+                let mut lines = vec![egui_plot::Line::new(PlotPoints::new(points)).name(name)];
+                if name == "velocity" {
+                    if let Some(numerical) = numerical_velocity_points(&states, cr.information.time_step_size) {
+                        lines.push(egui_plot::Line::new(numerical).name("numerical velocity"));
+                    }
                 }
+
+                plots.push((name, unit, lines));
+            }
+
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                ui.horizontal_top(|ui| {
+                    for (name, unit, lines) in plots {
+                        ui.vertical(|ui| {
+                            ui.label(egui::RichText::new(format!("{} [{}]", name, unit)).strong());
+
+                            egui_plot::Plot::new(("obstacle state plot", name))
+                                .legend(egui_plot::Legend::default().position(egui_plot::Corner::LeftBottom))
+                                .width(STATE_PLOT_WIDTH)
+                                .height(ui.available_height().max(75.0))
+                                .sharp_grid_lines(true)
+                                .link_axis(group, true, false)
+                                .link_cursor(group, true, false)
+                                .show(ui, |pui| {
+                                    for line in lines {
+                                        pui.line(line);
+                                    }
+                                    pui.vline(ts_vline.clone());
+
+                                    if pui.response().clicked() {
+                                        if let Some(pointer) = pui.pointer_coordinate() {
+                                            clicked_time_step = Some(pointer.x);
+                                        }
+                                    }
+                                });
+                        });
+                    }
+                });
             });
-    });
+        });
+
+    if let Some(ts) = clicked_time_step {
+        let ts = (ts.round() as f32).clamp(*cts.prediction_range.start(), *cts.prediction_range.end());
+        cts.dynamic_time_step = ts;
+    }
+
+    if !open {
+        selected.0 = None;
+    }
 }
 
 fn initial_position(obs: &DynamicObstacle) -> Option<Vec2> {
@@ -338,6 +530,8 @@ fn initial_position(obs: &DynamicObstacle) -> Option<Vec2> {
 pub fn spawn_obstacles(
     mut commands: Commands,
     cr: Res<crate::CommonRoad>,
+    args: Res<crate::args::Args>,
+    mut ts: ResMut<crate::global_settings::TimeStep>,
     mut camera_q: Query<&mut Transform, With<crate::MainCamera>>,
 ) {
     let mut max_ts = i32::MIN;
@@ -348,10 +542,29 @@ pub fn spawn_obstacles(
         }
     }
 
+    for obs in &cr.phantom_obstacles {
+        if let Some(obs_max_ts) = super::occupancy::spawn_phantom_obstacle(&mut commands, obs) {
+            max_ts = max_ts.max(obs_max_ts);
+        }
+    }
+
+    // Scenarios without any predicted obstacles only have a single time step
+    let last_time_step = max_ts.saturating_sub(1).max(0);
+
+    let time_step = (args.time_step as i32).min(last_time_step);
+    if time_step != args.time_step as i32 {
+        bevy::log::warn!(
+            "time step {} is after the last time step {} of the scenario, starting there instead",
+            args.time_step,
+            last_time_step
+        );
+    }
+
     commands.insert_resource(crate::global_settings::CurrentTimeStep {
-        dynamic_time_step: 0.0,
-        prediction_range: 0.0..=((max_ts - 1) as f32),
+        dynamic_time_step: time_step as f32,
+        prediction_range: 0.0..=(last_time_step as f32),
     });
+    ts.time_step = time_step;
 
     if let Some(obs) = cr.dynamic_obstacles.first() {
         let mut transform = camera_q
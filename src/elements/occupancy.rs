@@ -0,0 +1,125 @@
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb::{self, integer_exact_or_interval::ExactOrInterval};
+use crate::global_settings::{GlobalSettings, TimeStep};
+
+use super::shape::ShapeOutline;
+
+/// Future occupancies become fully transparent after this many time steps
+const FADE_TIME_STEPS: f32 = 30.0;
+
+/// Occupied area of a set-based prediction for some time steps.
+#[derive(Component)]
+pub(crate) struct Occupancy {
+    time_steps: std::ops::RangeInclusive<i32>,
+    color: Color,
+}
+
+pub(crate) fn time_step_range(time_step: &commonroad_pb::IntegerExactOrInterval) -> Option<std::ops::RangeInclusive<i32>> {
+    match time_step.exact_or_interval.as_ref()? {
+        ExactOrInterval::Exact(ts) => Some(*ts..=*ts),
+        ExactOrInterval::Interval(interval) => Some(interval.start..=interval.end),
+    }
+}
+
+/// Spawns the occupancies of a prediction as children of `parent`,
+/// returns the last time step with an occupancy.
+pub(crate) fn spawn_occupancies(
+    commands: &mut Commands,
+    parent: Entity,
+    label: &str,
+    prediction: &commonroad_pb::SetBasedPrediction,
+    color: Color,
+) -> Option<i32> {
+    let mut max_ts = None;
+
+    for occupancy in &prediction.occupancy_set.occupancies {
+        let Some(time_steps) = time_step_range(&occupancy.time_step) else {
+            bevy::log::warn!("skipping occupancy of {} without a time step", label);
+            continue;
+        };
+        max_ts = max_ts.max(Some(*time_steps.end()));
+
+        let description = if time_steps.start() == time_steps.end() {
+            format!("{} at t={}", label, time_steps.start())
+        } else {
+            format!("{} for t={}..={}", label, time_steps.start(), time_steps.end())
+        };
+
+        commands
+            .spawn((
+                Name::new(format!("occupancy for t={:?}", time_steps)),
+                ShapeBundle {
+                    // Occupancies are given in absolute coordinates,
+                    // later ones are drawn below the earlier ones
+                    path: GeometryBuilder::build_as(&ShapeOutline(&occupancy.shape)),
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, 3.0 - (*time_steps.start() as f32 * 1e-4)),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Fill::color(color),
+                Stroke::new(color.with_alpha(1.0), 0.05),
+                Occupancy { time_steps, color },
+                super::HoverTooltip::bundle(description),
+                RaycastPickable,
+            ))
+            .set_parent(parent);
+    }
+
+    max_ts
+}
+
+/// Spawns a phantom obstacle, which only consists of its predicted occupancies,
+/// returns the last time step with an occupancy.
+pub(crate) fn spawn_phantom_obstacle(commands: &mut Commands, obs: &commonroad_pb::PhantomObstacle) -> Option<i32> {
+    let prediction = obs.prediction.as_ref()?;
+
+    let group = commands
+        .spawn((
+            Name::new(format!("phantom obstacle {}", obs.obstacle_id)),
+            super::SceneEntity,
+            SpatialBundle::default(),
+        ))
+        .id();
+
+    let label = format!("Phantom obstacle {}", obs.obstacle_id);
+    spawn_occupancies(commands, group, &label, prediction, Color::srgba_u8(200, 60, 200, 110))
+}
+
+/// Shows the occupancies of the current time step, and optionally all future ones fading out.
+pub fn occupancy_visibility(
+    ts: Res<TimeStep>,
+    settings: Res<GlobalSettings>,
+    mut occupancy_q: Query<(Ref<Occupancy>, &mut Visibility, &mut Fill)>,
+) {
+    let current = ts.time_step;
+
+    for (occupancy, mut visibility, mut fill) in occupancy_q.iter_mut() {
+        if !ts.is_changed() && !settings.is_changed() && !occupancy.is_added() {
+            continue;
+        }
+
+        let time_steps = &occupancy.time_steps;
+
+        let future = *time_steps.start() > current && settings.show_future_occupancies;
+        let visible = time_steps.contains(&current) || future;
+
+        visibility.set_if_neq(if visible { Visibility::Inherited } else { Visibility::Hidden });
+
+        if visible {
+            let distance = (time_steps.start() - current).max(0) as f32;
+            let fade = (1.0 - distance / FADE_TIME_STEPS).max(0.0);
+            let alpha = occupancy.color.alpha() * fade;
+
+            fill.color = occupancy.color.with_alpha(alpha);
+        }
+    }
+}
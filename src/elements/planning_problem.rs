@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb::{self, float_exact_or_interval::ExactOrInterval};
+use crate::global_settings::CurrentTimeStep;
+
+use super::shape::ShapeOutline;
+use super::trajectory::MainTrajectory;
+
+/// Above the lanelets, but below all obstacles
+const GOAL_Z: f32 = 1.5;
+
+/// Above the obstacles, but below the trajectories
+const INITIAL_STATE_Z: f32 = 4.5;
+
+/// Goals given as a single point count as reached within this distance
+const POINT_GOAL_TOLERANCE: f32 = 0.5;
+
+/// Exact goal velocities and orientations count as reached within this tolerance
+const EXACT_GOAL_TOLERANCE: f64 = 1e-2;
+
+/// Region of the goal state of a planning problem.
+#[derive(Component)]
+pub(crate) struct GoalRegion {
+    problem_id: u32,
+    index: usize,
+    goal: commonroad_pb::GoalState,
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct HoveredGoal;
+
+/// First time step in which the main trajectory reached a goal of each planning problem.
+#[derive(Resource, Default)]
+pub(crate) struct GoalStatus {
+    /// Index of the reached goal and the time step, or `None` if no goal was reached
+    reached: HashMap<u32, Option<(usize, i32)>>,
+    last_time_step: Option<i32>,
+}
+
+fn describe_float(value: &Option<commonroad_pb::FloatExactOrInterval>, unit: &str) -> String {
+    match value.as_ref().and_then(|v| v.exact_or_interval.as_ref()) {
+        None => "any".to_owned(),
+        Some(ExactOrInterval::Exact(e)) => format!("{:.2} {}", e, unit),
+        Some(ExactOrInterval::Interval(i)) => format!("[{:.2}, {:.2}] {}", i.start, i.end, unit),
+    }
+}
+
+fn describe_time_step(time_step: &commonroad_pb::IntegerExactOrInterval) -> String {
+    match super::occupancy::time_step_range(time_step) {
+        None => "any".to_owned(),
+        Some(range) if range.start() == range.end() => range.start().to_string(),
+        Some(range) => format!("[{}, {}]", range.start(), range.end()),
+    }
+}
+
+fn float_interval(value: &Option<commonroad_pb::FloatExactOrInterval>) -> Option<(f64, f64)> {
+    match value.as_ref()?.exact_or_interval.as_ref()? {
+        ExactOrInterval::Exact(e) => Some((e - EXACT_GOAL_TOLERANCE, e + EXACT_GOAL_TOLERANCE)),
+        ExactOrInterval::Interval(i) => Some((i.start, i.end)),
+    }
+}
+
+fn angle_in_interval(angle: f64, (start, end): (f64, f64)) -> bool {
+    (angle - start).rem_euclid(std::f64::consts::TAU) <= end - start
+}
+
+/// Checks whether a state of the main trajectory satisfies a goal state.
+fn goal_reached(
+    goal: &commonroad_pb::GoalState,
+    goal_lanelets: &[Vec<Vec2>],
+    time_step: i32,
+    position: Vec2,
+    velocity: f32,
+    orientation: f32,
+) -> bool {
+    let state = &goal.state;
+
+    if let Some(range) = super::occupancy::time_step_range(&state.time_step) {
+        if !range.contains(&time_step) {
+            return false;
+        }
+    }
+
+    if let Some((start, end)) = float_interval(&state.velocity) {
+        if !(start..=end).contains(&(velocity as f64)) {
+            return false;
+        }
+    }
+
+    if let Some(interval) = float_interval(&state.orientation) {
+        if !angle_in_interval(orientation as f64, interval) {
+            return false;
+        }
+    }
+
+    let in_position = match &state.position {
+        Some(commonroad_pb::state::Position::Shape(shape)) => super::shape::shape_contains(shape, position),
+        Some(commonroad_pb::state::Position::Point(point)) => {
+            Vec2::from(point).distance(position) <= POINT_GOAL_TOLERANCE
+        }
+        None => goal_lanelets.is_empty(),
+    };
+    let in_lanelets = goal_lanelets
+        .iter()
+        .any(|outline| super::shape::polygon_contains(outline, position));
+
+    in_position || in_lanelets
+}
+
+fn goal_lanelet_outlines(cr: &commonroad_pb::CommonRoad, goal: &commonroad_pb::GoalState) -> Vec<Vec<Vec2>> {
+    goal.goal_position_lanelets
+        .iter()
+        .filter_map(|id| {
+            let lanelet = cr.lanelets.iter().find(|l| l.lanelet_id == *id);
+            if lanelet.is_none() {
+                bevy::log::warn!("goal refers to unknown lanelet {}", id);
+            }
+            lanelet
+        })
+        .map(super::lanelet::lanelet_outline)
+        .collect()
+}
+
+fn spawn_goal(
+    commands: &mut Commands,
+    parent: Entity,
+    cr: &commonroad_pb::CommonRoad,
+    problem_id: u32,
+    index: usize,
+    goal: &commonroad_pb::GoalState,
+) {
+    let mut paths = vec![];
+
+    match &goal.state.position {
+        Some(commonroad_pb::state::Position::Shape(shape)) => {
+            paths.push(GeometryBuilder::build_as(&ShapeOutline(shape)));
+        }
+        Some(commonroad_pb::state::Position::Point(point)) => {
+            paths.push(GeometryBuilder::build_as(&shapes::Circle {
+                radius: POINT_GOAL_TOLERANCE,
+                center: point.into(),
+            }));
+        }
+        None => {}
+    }
+
+    for outline in goal_lanelet_outlines(cr, goal) {
+        paths.push(GeometryBuilder::build_as(&shapes::Polygon {
+            points: outline,
+            closed: true,
+        }));
+    }
+
+    if paths.is_empty() {
+        bevy::log::info!("goal {} of planning problem {} has no position", index, problem_id);
+    }
+
+    let color = bevy::color::palettes::css::LIME_GREEN;
+
+    for path in paths {
+        commands
+            .spawn((
+                Name::new(format!("goal {}", index)),
+                GoalRegion {
+                    problem_id,
+                    index,
+                    goal: goal.to_owned(),
+                },
+                ShapeBundle {
+                    path,
+                    spatial: SpatialBundle {
+                        transform: Transform::from_xyz(0.0, 0.0, GOAL_Z),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Fill::color(color.with_alpha(0.3)),
+                Stroke::new(color, 0.15),
+                PickableBundle::default(),
+                RaycastPickable,
+                On::<Pointer<Over>>::target_insert(HoveredGoal),
+                On::<Pointer<Out>>::target_remove::<HoveredGoal>(),
+            ))
+            .set_parent(parent);
+    }
+}
+
+/// Arrow marking the initial state of a planning problem.
+fn spawn_initial_state(
+    commands: &mut Commands,
+    group: Entity,
+    problem: &commonroad_pb::PlanningProblem,
+    mut transform: Transform,
+) {
+    transform.translation.z = INITIAL_STATE_Z;
+
+    let marker = shapes::Polygon {
+        points: vec![
+            Vec2::new(2.5, 0.0),
+            Vec2::new(-2.0, 1.2),
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(-2.0, -1.2),
+        ],
+        closed: true,
+    };
+
+    commands
+        .spawn((
+            Name::new("initial state"),
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&marker),
+                spatial: SpatialBundle {
+                    transform,
+                    ..default()
+                },
+                ..default()
+            },
+            Fill::color(bevy::color::palettes::css::DEEP_SKY_BLUE),
+            Stroke::new(bevy::color::palettes::css::NAVY, 0.1),
+            super::HoverTooltip::bundle(format!(
+                "Initial state of planning problem {} (t={}, v={})",
+                problem.planning_problem_id,
+                describe_time_step(&problem.initial_state.time_step),
+                describe_float(&problem.initial_state.velocity, "m/s"),
+            )),
+            RaycastPickable,
+        ))
+        .set_parent(group);
+}
+
+fn spawn_planning_problem(
+    commands: &mut Commands,
+    cr: &commonroad_pb::CommonRoad,
+    problem: &commonroad_pb::PlanningProblem,
+) {
+    let id = problem.planning_problem_id;
+
+    let group = commands
+        .spawn((
+            Name::new(format!("planning problem {}", id)),
+            super::SceneEntity,
+            SpatialBundle::default(),
+        ))
+        .id();
+
+    // The goals are shown even without an exact initial state
+    match super::obstacle::state_transform(&problem.initial_state) {
+        Some(transform) => spawn_initial_state(commands, group, problem, transform),
+        None => bevy::log::warn!("planning problem {} has no exact initial position and orientation", id),
+    }
+
+    for (index, goal) in problem.goal_states.iter().enumerate() {
+        spawn_goal(commands, group, cr, id, index, goal);
+    }
+}
+
+pub fn spawn_planning_problems(mut commands: Commands, cr: Res<crate::CommonRoad>) {
+    for problem in &cr.planning_problems {
+        spawn_planning_problem(&mut commands, &cr, problem);
+    }
+}
+
+/// Checks when the main trajectory first reaches the goal of each planning problem.
+pub(crate) fn update_goal_status(
+    mut commands: Commands,
+    cr: Res<crate::CommonRoad>,
+    mtraj: Res<MainTrajectory>,
+) {
+    let mut status = GoalStatus {
+        last_time_step: mtraj.states().last().map(|(ts, ..)| ts),
+        ..default()
+    };
+
+    for problem in &cr.planning_problems {
+        let goals: Vec<_> = problem
+            .goal_states
+            .iter()
+            .map(|goal| (goal, goal_lanelet_outlines(&cr, goal)))
+            .collect();
+
+        let reached = mtraj.states().find_map(|(ts, pos, velocity, orientation)| {
+            goals
+                .iter()
+                .position(|(goal, lanelets)| goal_reached(goal, lanelets, ts, pos, velocity, orientation))
+                .map(|index| (index, ts))
+        });
+
+        status.reached.insert(problem.planning_problem_id, reached);
+    }
+
+    commands.insert_resource(status);
+}
+
+fn status_label(ui: &mut egui::Ui, status: Option<&GoalStatus>, problem_id: u32) -> Option<i32> {
+    let Some(status) = status else {
+        ui.label("No planner logs loaded");
+        return None;
+    };
+
+    match status.reached.get(&problem_id).copied().flatten() {
+        Some((index, ts)) => {
+            ui.label(
+                egui::RichText::new(format!("Goal {} reached at t={}", index, ts))
+                    .color(egui::Color32::LIGHT_GREEN),
+            );
+            Some(ts)
+        }
+        None => {
+            let text = match status.last_time_step {
+                Some(last) => format!("Goal not reached up to t={}", last),
+                None => "Goal not reached".to_owned(),
+            };
+            ui.label(egui::RichText::new(text).color(egui::Color32::LIGHT_RED));
+            None
+        }
+    }
+}
+
+pub fn goal_tooltip(
+    mut contexts: EguiContexts,
+    status: Option<Res<GoalStatus>>,
+
+    goal_q: Query<&GoalRegion, With<HoveredGoal>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    let base_id = egui::Id::new("goal tooltip");
+    let layer_id = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("goal tooltips"));
+
+    for region in goal_q.iter() {
+        let id = base_id.with((region.problem_id, region.index));
+        egui::containers::show_tooltip(ctx, layer_id, id, |ui| {
+            ui.heading(format!(
+                "Goal {} of planning problem {}",
+                region.index, region.problem_id
+            ));
+
+            let state = &region.goal.state;
+            ui.label(format!("time step: {}", describe_time_step(&state.time_step)));
+            ui.label(format!("velocity: {}", describe_float(&state.velocity, "m/s")));
+            ui.label(format!("orientation: {}", describe_float(&state.orientation, "rad")));
+            if !region.goal.goal_position_lanelets.is_empty() {
+                ui.label(format!("lanelets: {:?}", region.goal.goal_position_lanelets));
+            }
+
+            ui.separator();
+            status_label(ui, status.as_deref(), region.problem_id);
+        });
+    }
+}
+
+pub fn planning_problem_window(
+    mut contexts: EguiContexts,
+    cr: Res<crate::CommonRoad>,
+    status: Option<Res<GoalStatus>>,
+    mut cts: ResMut<CurrentTimeStep>,
+) {
+    if cr.planning_problems.is_empty() {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new("\u{1F3C1} Planning Problems")
+        .default_open(false)
+        .show(ctx, |ui| {
+            for problem in &cr.planning_problems {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("#{}", problem.planning_problem_id)).strong());
+
+                    if let Some(ts) = status_label(ui, status.as_deref(), problem.planning_problem_id) {
+                        if ui.small_button("show").clicked() {
+                            cts.dynamic_time_step = ts as f32;
+                        }
+                    }
+                });
+            }
+        });
+}
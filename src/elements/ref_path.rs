@@ -4,6 +4,8 @@ use bevy_prototype_lyon::prelude::*;
 
 use bevy_mod_picking::prelude::*;
 
+use crate::loading::LoadError;
+
 #[derive(Debug, miniserde::Deserialize, Clone, Component, Reflect)]
 pub struct RefPath {
     x: Vec<f32>,
@@ -26,8 +28,17 @@ pub fn ref_path_tooltip(mut contexts: bevy_egui::EguiContexts, ref_path_q: Query
     }
 }
 
-fn read_ref_path(args: &crate::args::Args) -> Result<RefPath, Box<dyn std::error::Error>> {
-    let db_path = std::path::Path::join(&args.logs, "trajectories.db");
+fn read_ref_path_file(path: &std::path::Path) -> Result<RefPath, LoadError> {
+    let rstr = std::fs::read_to_string(path).map_err(LoadError::io(path))?;
+    let data: RefPath = miniserde::json::from_str(&rstr)
+        .map_err(|_| LoadError::json(path.display().to_string()))?;
+
+    Ok(data)
+}
+
+fn read_ref_path_db(db_path: &std::path::Path) -> Result<RefPath, LoadError> {
+    use rusqlite::OptionalExtension;
+
     let conn = rusqlite::Connection::open_with_flags(
         db_path,
         rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX
@@ -38,18 +49,12 @@ fn read_ref_path(args: &crate::args::Args) -> Result<RefPath, Box<dyn std::error
             "SELECT value FROM meta WHERE key = 'reference_path'"
         )?;
 
-        stmt.query_row([], |row| {
-            let rusqlite::types::ValueRef::Text(st) = row.get_ref(0)? else { todo!() };
-            let rstr = std::str::from_utf8(st)?;
-            let data: RefPath = miniserde::json::from_str(rstr).map_err(|err| {
-                return rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err));
-            })?;
-
-            Ok(data)
-        })?
+        stmt.query_row([], |row| crate::loading::json_column(row, "value"))
+            .optional()?
+            .ok_or_else(|| LoadError::schema("the meta table has no 'reference_path' entry"))?
     };
 
-    conn.close().unwrap();
+    conn.close().map_err(|(_conn, err)| err)?;
 
     // let file = std::fs::File::open(path).unwrap();
 
@@ -70,11 +75,30 @@ fn read_ref_path(args: &crate::args::Args) -> Result<RefPath, Box<dyn std::error
     Ok(data)
 }
 
-pub fn spawn_ref_path(mut commands: Commands, args: Res<crate::args::Args>) {
-    let rp = match read_ref_path(&args) {
+fn read_ref_path(args: &crate::args::Args) -> Option<Result<RefPath, LoadError>> {
+    if let Some(path) = &args.reference_path {
+        return Some(read_ref_path_file(path));
+    }
+
+    let db_path = args.trajectories_db()?;
+
+    Some(read_ref_path_db(&db_path))
+}
+
+pub fn spawn_ref_path(
+    mut commands: Commands,
+    args: Res<crate::args::Args>,
+    mut diagnostics: ResMut<crate::loading::LoadDiagnostics>,
+) {
+    let Some(rp) = read_ref_path(&args) else {
+        bevy::log::info!("no logs directory or reference path given, skipping reference path");
+        return;
+    };
+
+    let rp = match rp {
         Ok(rp) => rp,
         Err(e) => {
-            bevy::log::error!("Failed to read reference path: {}", e);
+            diagnostics.warn("reference path", e);
             return;
         }
     };
@@ -94,6 +118,7 @@ pub fn spawn_ref_path(mut commands: Commands, args: Res<crate::args::Args>) {
 
     commands.spawn((
         Name::new("reference path"),
+        super::SceneEntity,
         ShapeBundle {
             path: GeometryBuilder::build_as(&reference_path_shape),
             spatial: SpatialBundle {
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::prelude::*;
+
+use lyon_path::math::Point;
+
+use crate::commonroad_pb;
+
+/// Outline of a CommonRoad [`Shape`](commonroad_pb::Shape),
+/// relative to the position and orientation of the state it belongs to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ShapeOutline<'a>(pub(crate) &'a commonroad_pb::Shape);
+
+fn to_point(v: Vec2) -> Point {
+    Point::new(v.x, v.y)
+}
+
+fn add_shape(b: &mut lyon_path::path::Builder, shape: &commonroad_pb::Shape) {
+    use commonroad_pb::shape::Shape;
+
+    match shape.shape.as_ref() {
+        Some(Shape::Rectangle(rect)) => {
+            let half_extents = Vec2::new(rect.length as f32, rect.width as f32) / 2.0;
+            let center = rect.center.as_ref().map_or(Vec2::ZERO, Vec2::from);
+            let rotation = Vec2::from_angle(rect.orientation.unwrap_or(0.0) as f32);
+
+            let corners = [
+                Vec2::new(-half_extents.x, -half_extents.y),
+                Vec2::new(half_extents.x, -half_extents.y),
+                Vec2::new(half_extents.x, half_extents.y),
+                Vec2::new(-half_extents.x, half_extents.y),
+            ]
+            .map(|corner| to_point(center + rotation.rotate(corner)));
+
+            b.add_polygon(lyon_path::Polygon {
+                points: &corners,
+                closed: true,
+            });
+        }
+        Some(Shape::Circle(circle)) => {
+            let center = circle.center.as_ref().map_or(Vec2::ZERO, Vec2::from);
+
+            b.add_circle(to_point(center), circle.radius as f32, lyon_path::Winding::Positive);
+        }
+        Some(Shape::Polygon(polygon)) => {
+            if polygon.vertices.len() < 3 {
+                bevy::log::warn!("skipping polygon with only {} vertices", polygon.vertices.len());
+                return;
+            }
+
+            let points: Vec<Point> = polygon
+                .vertices
+                .iter()
+                .map(|vertex| to_point(vertex.into()))
+                .collect();
+
+            b.add_polygon(lyon_path::Polygon {
+                points: &points,
+                closed: true,
+            });
+        }
+        Some(Shape::ShapeGroup(group)) => {
+            for shape in &group.shapes {
+                add_shape(b, shape);
+            }
+        }
+        None => {
+            bevy::log::warn!("skipping empty shape");
+        }
+    }
+}
+
+/// Checks whether a point lies inside of a polygon, using the even-odd rule.
+pub(crate) fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+
+    for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+/// Checks whether a point lies inside of a shape given in absolute coordinates.
+pub(crate) fn shape_contains(shape: &commonroad_pb::Shape, point: Vec2) -> bool {
+    use commonroad_pb::shape::Shape;
+
+    match shape.shape.as_ref() {
+        Some(Shape::Rectangle(rect)) => {
+            let half_extents = Vec2::new(rect.length as f32, rect.width as f32) / 2.0;
+            let center = rect.center.as_ref().map_or(Vec2::ZERO, Vec2::from);
+            let rotation = Vec2::from_angle(-rect.orientation.unwrap_or(0.0) as f32);
+
+            let local = rotation.rotate(point - center);
+            local.x.abs() <= half_extents.x && local.y.abs() <= half_extents.y
+        }
+        Some(Shape::Circle(circle)) => {
+            let center = circle.center.as_ref().map_or(Vec2::ZERO, Vec2::from);
+
+            center.distance(point) <= circle.radius as f32
+        }
+        Some(Shape::Polygon(polygon)) => {
+            let points: Vec<Vec2> = polygon.vertices.iter().map(Vec2::from).collect();
+
+            polygon_contains(&points, point)
+        }
+        Some(Shape::ShapeGroup(group)) => group.shapes.iter().any(|shape| shape_contains(shape, point)),
+        None => false,
+    }
+}
+
+impl Geometry for ShapeOutline<'_> {
+    fn add_geometry(&self, b: &mut lyon_path::path::Builder) {
+        add_shape(b, self.0);
+    }
+}
@@ -0,0 +1,305 @@
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb;
+use crate::global_settings::TimeStep;
+
+use super::obstacle::ObstacleData;
+
+/// Blinking lights switch on and off this many times per second
+const BLINK_FREQUENCY: f32 = 1.5;
+
+/// Above the obstacle shape
+const SIGNAL_LIGHT_Z: f32 = 0.05;
+
+/// Signal of an obstacle that is shown on its shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Signal {
+    IndicatorLeft,
+    IndicatorRight,
+    BrakingLights,
+    FlashingBlueLights,
+    Horn,
+}
+
+impl Signal {
+    fn is_on(self, state: &commonroad_pb::SignalState) -> bool {
+        let hazard = state.hazard_warning_lights.unwrap_or(false);
+
+        match self {
+            Signal::IndicatorLeft => hazard || state.indicator_left.unwrap_or(false),
+            Signal::IndicatorRight => hazard || state.indicator_right.unwrap_or(false),
+            Signal::BrakingLights => state.braking_lights.unwrap_or(false),
+            Signal::FlashingBlueLights => state.flashing_blue_lights.unwrap_or(false),
+            Signal::Horn => state.horn.unwrap_or(false),
+        }
+    }
+
+    fn blinks(self) -> bool {
+        matches!(
+            self,
+            Signal::IndicatorLeft | Signal::IndicatorRight | Signal::FlashingBlueLights
+        )
+    }
+}
+
+/// Light on an obstacle shape, shown while its signal is on.
+#[derive(Component)]
+pub(crate) struct SignalLight {
+    signal: Signal,
+    /// Blinks in the opposite phase, like the second of the blue lights
+    alternate: bool,
+    /// Whether the signal is on at the current time step
+    on: bool,
+}
+
+fn time_step_start(state: &commonroad_pb::SignalState) -> Option<i32> {
+    let range = super::occupancy::time_step_range(state.time_step.as_ref()?)?;
+    Some(*range.start())
+}
+
+/// Signal state of an obstacle at a time step, from its initial signal state and signal series.
+///
+/// Signals keep their last known value until the series changes them,
+/// signals that were never given stay `None`.
+pub(crate) fn signal_state_at(
+    initial_signal_state: Option<&commonroad_pb::SignalState>,
+    signal_series: &[commonroad_pb::SignalState],
+    time_step: i32,
+) -> commonroad_pb::SignalState {
+    let mut states: Vec<(i32, &commonroad_pb::SignalState)> = initial_signal_state
+        .into_iter()
+        .chain(signal_series)
+        .filter_map(|state| Some((time_step_start(state)?, state)))
+        .filter(|(ts, _)| *ts <= time_step)
+        .collect();
+    states.sort_by_key(|(ts, _)| *ts);
+
+    let mut current = commonroad_pb::SignalState::default();
+    for (_, state) in states {
+        current.horn = state.horn.or(current.horn);
+        current.indicator_left = state.indicator_left.or(current.indicator_left);
+        current.indicator_right = state.indicator_right.or(current.indicator_right);
+        current.braking_lights = state.braking_lights.or(current.braking_lights);
+        current.hazard_warning_lights = state.hazard_warning_lights.or(current.hazard_warning_lights);
+        current.flashing_blue_lights = state.flashing_blue_lights.or(current.flashing_blue_lights);
+    }
+
+    current
+}
+
+pub(crate) fn has_signals(
+    initial_signal_state: Option<&commonroad_pb::SignalState>,
+    signal_series: &[commonroad_pb::SignalState],
+) -> bool {
+    initial_signal_state.is_some() || !signal_series.is_empty()
+}
+
+/// Readable rows of a signal state, for tooltips.
+pub(crate) fn signal_rows(state: &commonroad_pb::SignalState) -> [(&'static str, Option<bool>); 6] {
+    [
+        ("indicator left", state.indicator_left),
+        ("indicator right", state.indicator_right),
+        ("hazard warning lights", state.hazard_warning_lights),
+        ("braking lights", state.braking_lights),
+        ("flashing blue lights", state.flashing_blue_lights),
+        ("horn", state.horn),
+    ]
+}
+
+/// Table of the signals of an obstacle at a time step, for tooltips.
+pub(crate) fn signal_grid(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    state: &commonroad_pb::SignalState,
+    time_step: i32,
+) {
+    egui::Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+        ui.label(egui::RichText::new("signal").strong());
+        ui.label(egui::RichText::new(format!("t={}", time_step)).strong());
+        ui.end_row();
+
+        for (name, value) in signal_rows(state) {
+            ui.label(name);
+            match value {
+                Some(true) => ui.colored_label(egui::Color32::LIGHT_GREEN, "on"),
+                Some(false) => ui.label("off"),
+                None => ui.weak("unknown"),
+            };
+            ui.end_row();
+        }
+    });
+}
+
+/// Frame of the obstacle shape the lights are placed in, and its half extents.
+///
+/// The x axis points to the front of the obstacle.
+fn light_frame(shape: &commonroad_pb::Shape) -> Option<(Transform, Vec2)> {
+    use commonroad_pb::shape::Shape;
+
+    match shape.shape.as_ref()? {
+        Shape::Rectangle(rect) => {
+            let center = rect.center.as_ref().map_or(Vec2::ZERO, Vec2::from);
+            let transform = Transform::from_translation(center.extend(0.0))
+                .with_rotation(Quat::from_rotation_z(rect.orientation.unwrap_or(0.0) as f32));
+
+            Some((transform, Vec2::new(rect.length as f32, rect.width as f32) / 2.0))
+        }
+        Shape::Circle(circle) => {
+            let center = circle.center.as_ref().map_or(Vec2::ZERO, Vec2::from);
+
+            Some((
+                Transform::from_translation(center.extend(0.0)),
+                Vec2::splat(circle.radius as f32),
+            ))
+        }
+        Shape::Polygon(polygon) => {
+            let vertices: Vec<Vec2> = polygon.vertices.iter().map(Vec2::from).collect();
+            let min = vertices.iter().copied().reduce(Vec2::min)?;
+            let max = vertices.iter().copied().reduce(Vec2::max)?;
+
+            Some((
+                Transform::from_translation(((min + max) / 2.0).extend(0.0)),
+                (max - min) / 2.0,
+            ))
+        }
+        Shape::ShapeGroup(group) => light_frame(group.shapes.first()?),
+    }
+}
+
+/// Spawns the signal lights of an obstacle as children of its shape, hidden until their signal is on.
+pub(crate) fn spawn_signal_lights(commands: &mut Commands, parent: Entity, obs: &commonroad_pb::DynamicObstacle) {
+    use bevy::color::palettes::css;
+
+    if !has_signals(obs.initial_signal_state.as_ref(), &obs.signal_series) {
+        return;
+    }
+
+    let Some((frame, half_extents)) = light_frame(&obs.shape) else {
+        bevy::log::warn!("obstacle {} has signals, but no shape to show them on", obs.dynamic_obstacle_id);
+        return;
+    };
+    let (hx, hy) = (half_extents.x, half_extents.y);
+
+    let radius = (hy * 0.3).min(0.35);
+    let circle = |center: Vec2| GeometryBuilder::build_as(&shapes::Circle { radius, center });
+    let bar_width = (hx * 0.15).min(0.3);
+
+    let lights = [
+        (Signal::IndicatorLeft, false, circle(Vec2::new(hx - radius, hy - radius)), css::ORANGE),
+        (Signal::IndicatorLeft, false, circle(Vec2::new(radius - hx, hy - radius)), css::ORANGE),
+        (Signal::IndicatorRight, false, circle(Vec2::new(hx - radius, radius - hy)), css::ORANGE),
+        (Signal::IndicatorRight, false, circle(Vec2::new(radius - hx, radius - hy)), css::ORANGE),
+        (
+            Signal::BrakingLights,
+            false,
+            GeometryBuilder::build_as(&shapes::Rectangle {
+                extents: Vec2::new(bar_width, hy * 1.6),
+                origin: shapes::RectangleOrigin::CustomCenter(Vec2::new(bar_width / 2.0 - hx, 0.0)),
+            }),
+            css::RED,
+        ),
+        (Signal::FlashingBlueLights, false, circle(Vec2::new(0.0, hy * 0.4)), css::BLUE),
+        (Signal::FlashingBlueLights, true, circle(Vec2::new(0.0, -hy * 0.4)), css::BLUE),
+    ];
+
+    for (signal, alternate, path, color) in lights {
+        commands
+            .spawn((
+                Name::new(format!("signal {:?}", signal)),
+                SignalLight {
+                    signal,
+                    alternate,
+                    on: false,
+                },
+                ShapeBundle {
+                    path,
+                    spatial: SpatialBundle {
+                        transform: frame * Transform::from_xyz(0.0, 0.0, SIGNAL_LIGHT_Z),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Fill::color(color),
+                Stroke::new(Color::BLACK, 0.05),
+            ))
+            .set_parent(parent);
+    }
+
+    // Ring around the front of the obstacle, drawn below its shape
+    commands
+        .spawn((
+            Name::new(format!("signal {:?}", Signal::Horn)),
+            SignalLight {
+                signal: Signal::Horn,
+                alternate: false,
+                on: false,
+            },
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::Circle {
+                    radius: hy.max(0.5),
+                    center: Vec2::new(hx, 0.0),
+                }),
+                spatial: SpatialBundle {
+                    transform: frame * Transform::from_xyz(0.0, 0.0, -SIGNAL_LIGHT_Z),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ..default()
+            },
+            Stroke::new(css::YELLOW, 0.1),
+        ))
+        .set_parent(parent);
+}
+
+/// Shows the lights of the signals that are on at the current time step, blinking in real time.
+pub(crate) fn signal_lights(
+    time: Res<Time>,
+    ts: Res<TimeStep>,
+    mut redraw_request_events: EventWriter<bevy::window::RequestRedraw>,
+
+    obstacle_q: Query<(&ObstacleData, &InheritedVisibility)>,
+    new_light_q: Query<(), Added<SignalLight>>,
+    mut light_q: Query<(&mut SignalLight, &Parent, &mut Visibility)>,
+) {
+    // The signal states only change with the time step, every frame only switches the blinking lights
+    if ts.is_changed() || !new_light_q.is_empty() {
+        let mut states = bevy::utils::HashMap::new();
+
+        for (mut light, parent, _visibility) in light_q.iter_mut() {
+            let Ok((ObstacleData(obs), _obstacle_visibility)) = obstacle_q.get(parent.get()) else {
+                continue;
+            };
+
+            let state = states.entry(parent.get()).or_insert_with(|| {
+                signal_state_at(obs.initial_signal_state.as_ref(), &obs.signal_series, ts.time_step)
+            });
+            light.on = light.signal.is_on(state);
+        }
+    }
+
+    let blink_phase = (time.elapsed_seconds() * BLINK_FREQUENCY * 2.0) as u64 % 2 == 0;
+    let mut blinking = false;
+
+    for (light, parent, mut visibility) in light_q.iter_mut() {
+        let Ok((_obs, obstacle_visibility)) = obstacle_q.get(parent.get()) else {
+            continue;
+        };
+
+        let lit = if light.on && light.signal.blinks() {
+            blinking |= obstacle_visibility.get();
+            blink_phase != light.alternate
+        } else {
+            light.on
+        };
+
+        visibility.set_if_neq(if lit { Visibility::Inherited } else { Visibility::Hidden });
+    }
+
+    // Keep updating while a visible light blinks, even without any input
+    if blinking {
+        redraw_request_events.send(bevy::window::RequestRedraw);
+    }
+}
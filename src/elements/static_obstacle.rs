@@ -0,0 +1,103 @@
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb::{self, obstacle_type_enum::ObstacleType};
+
+use super::obstacle::HoveredObstacle;
+use super::shape::ShapeOutline;
+
+#[derive(Component)]
+pub struct StaticObstacleData(commonroad_pb::StaticObstacle);
+
+/// Fill and stroke colors of a static obstacle.
+fn obstacle_style(obstacle_type: ObstacleType) -> (Color, Color) {
+    use bevy::color::palettes::css;
+
+    match obstacle_type {
+        ObstacleType::ParkedVehicle => (Color::srgb_u8(170, 180, 195), css::SLATE_GRAY.into()),
+        ObstacleType::ConstructionZone => (Color::srgba_u8(255, 140, 0, 180), css::DARK_RED.into()),
+        ObstacleType::RoadBoundary => (Color::srgb_u8(60, 60, 60), css::BLACK.into()),
+        _ => (Color::srgb_u8(220, 220, 220), css::DIM_GRAY.into()),
+    }
+}
+
+fn spawn_static_obstacle(commands: &mut Commands, obs: &commonroad_pb::StaticObstacle) {
+    let mut transform = super::obstacle::state_transform(&obs.initial_state).unwrap_or_else(|| {
+        bevy::log::warn!(
+            "static obstacle {} has no exact initial position and orientation",
+            obs.static_obstacle_id
+        );
+        Transform::IDENTITY
+    });
+    // Below the dynamic obstacles
+    transform.translation.z = 3.5;
+
+    let (fill, stroke) = obstacle_style(obs.obstacle_type());
+
+    commands.spawn((
+        Name::new(format!("static obstacle {}", obs.static_obstacle_id)),
+        super::SceneEntity,
+        StaticObstacleData(obs.to_owned()),
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&ShapeOutline(&obs.shape)),
+            spatial: SpatialBundle {
+                transform,
+                ..default()
+            },
+            ..default()
+        },
+        Fill::color(fill),
+        Stroke::new(stroke, 0.15),
+        PickableBundle::default(),
+        RaycastPickable,
+        On::<Pointer<Over>>::target_insert(HoveredObstacle),
+        On::<Pointer<Out>>::target_remove::<HoveredObstacle>(),
+    ));
+}
+
+pub fn spawn_static_obstacles(mut commands: Commands, cr: Res<crate::CommonRoad>) {
+    for obs in &cr.static_obstacles {
+        spawn_static_obstacle(&mut commands, obs);
+    }
+}
+
+pub fn static_obstacle_tooltip(
+    mut contexts: EguiContexts,
+    ts: Res<crate::global_settings::TimeStep>,
+
+    obstacle_q: Query<(&StaticObstacleData, &Transform), With<HoveredObstacle>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    let base_id = egui::Id::new("static obstacle tooltip");
+    let layer_id = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("obstacle tooltips"));
+
+    for (StaticObstacleData(obs), transform) in obstacle_q.iter() {
+        egui::containers::show_tooltip(ctx, layer_id, base_id.with(obs.static_obstacle_id), |ui| {
+            ui.heading(format!(
+                "Static Obstacle {} (type {:#?})",
+                obs.static_obstacle_id,
+                obs.obstacle_type()
+            ));
+
+            ui.label(format!(
+                "position: ({:.2}, {:.2})",
+                transform.translation.x, transform.translation.y
+            ));
+
+            if !super::signal::has_signals(obs.initial_signal_state.as_ref(), &obs.signal_series) {
+                return;
+            }
+
+            let state =
+                super::signal::signal_state_at(obs.initial_signal_state.as_ref(), &obs.signal_series, ts.time_step);
+            super::signal::signal_grid(ui, ("static obstacle signals", obs.static_obstacle_id), &state, ts.time_step);
+        });
+    }
+}
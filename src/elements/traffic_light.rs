@@ -0,0 +1,296 @@
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb::{self, traffic_light_state_enum::TrafficLightState};
+use crate::global_settings::TimeStep;
+
+/// Above the lanelets and environment obstacles, but below the occupancies
+const TRAFFIC_LIGHT_Z: f32 = 2.5;
+
+#[derive(Component)]
+pub struct TrafficLightData(commonroad_pb::TrafficLight);
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct HoveredTrafficLight;
+
+/// State of a traffic light at a time step, following its cycle from the time offset on.
+fn state_at(light: &commonroad_pb::TrafficLight, time_step: i32) -> TrafficLightState {
+    if light.active == Some(false) {
+        return TrafficLightState::Inactive;
+    }
+
+    let cycle_duration: i64 = light.cycle_elements.iter().map(|e| e.duration as i64).sum();
+    if cycle_duration == 0 {
+        return TrafficLightState::Inactive;
+    }
+
+    let mut t = (time_step as i64 - light.time_offset.unwrap_or(0) as i64).rem_euclid(cycle_duration);
+    for element in &light.cycle_elements {
+        if t < element.duration as i64 {
+            return element.color();
+        }
+        t -= element.duration as i64;
+    }
+
+    unreachable!("time step is within the cycle duration")
+}
+
+fn state_color(state: TrafficLightState) -> Color {
+    use bevy::color::palettes::css;
+
+    match state {
+        TrafficLightState::Red => css::RED.into(),
+        TrafficLightState::RedYellow => css::ORANGE_RED.into(),
+        TrafficLightState::Green => css::LIME.into(),
+        TrafficLightState::Yellow => css::YELLOW.into(),
+        TrafficLightState::Inactive => css::DIM_GRAY.into(),
+    }
+}
+
+fn center(points: &[commonroad_pb::Point]) -> Option<Vec2> {
+    if points.is_empty() {
+        return None;
+    }
+
+    Some(points.iter().map(Vec2::from).sum::<Vec2>() / points.len() as f32)
+}
+
+/// Positions to show a traffic light at, either its own or those of the lanelets referring to it.
+fn traffic_light_positions(cr: &commonroad_pb::CommonRoad, light: &commonroad_pb::TrafficLight) -> Vec<Vec2> {
+    if let Some(position) = &light.position {
+        return vec![position.into()];
+    }
+
+    let id = light.traffic_light_id;
+    let mut positions = vec![];
+
+    for lanelet in &cr.lanelets {
+        let stop_line = lanelet
+            .stop_line
+            .as_ref()
+            .filter(|stop_line| stop_line.traffic_light_refs.contains(&id));
+
+        let position = if let Some(stop_line) = stop_line {
+            center(&stop_line.points)
+        } else if lanelet.traffic_light_refs.contains(&id) {
+            super::lanelet::lanelet_end(lanelet)
+        } else {
+            None
+        };
+
+        positions.extend(position);
+    }
+
+    positions
+}
+
+fn spawn_traffic_light(commands: &mut Commands, cr: &commonroad_pb::CommonRoad, light: &commonroad_pb::TrafficLight) {
+    let positions = traffic_light_positions(cr, light);
+    if positions.is_empty() {
+        bevy::log::warn!(
+            "traffic light {} has no position and is not referenced by any lanelet",
+            light.traffic_light_id
+        );
+    }
+
+    for position in positions {
+        commands.spawn((
+            Name::new(format!("traffic light {}", light.traffic_light_id)),
+            super::SceneEntity,
+            TrafficLightData(light.to_owned()),
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::Circle {
+                    radius: 0.8,
+                    center: Vec2::ZERO,
+                }),
+                spatial: SpatialBundle {
+                    transform: Transform::from_translation(position.extend(TRAFFIC_LIGHT_Z)),
+                    ..default()
+                },
+                ..default()
+            },
+            Fill::color(state_color(TrafficLightState::Inactive)),
+            Stroke::new(Color::BLACK, 0.2),
+            PickableBundle::default(),
+            RaycastPickable,
+            On::<Pointer<Over>>::target_insert(HoveredTrafficLight),
+            On::<Pointer<Out>>::target_remove::<HoveredTrafficLight>(),
+        ));
+    }
+}
+
+pub fn spawn_traffic_lights(mut commands: Commands, cr: Res<crate::CommonRoad>) {
+    for light in &cr.traffic_lights {
+        spawn_traffic_light(&mut commands, &cr, light);
+    }
+}
+
+pub fn traffic_light_colors(
+    ts: Res<TimeStep>,
+    mut light_q: Query<(Ref<TrafficLightData>, &mut Fill)>,
+) {
+    for (light, mut fill) in light_q.iter_mut() {
+        if !ts.is_changed() && !light.is_added() {
+            continue;
+        }
+
+        let color = state_color(state_at(&light.0, ts.time_step));
+        if fill.color != color {
+            fill.color = color;
+        }
+    }
+}
+
+pub fn traffic_light_tooltip(
+    mut contexts: EguiContexts,
+    ts: Res<TimeStep>,
+
+    light_q: Query<&TrafficLightData, With<HoveredTrafficLight>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    let base_id = egui::Id::new("traffic light tooltip");
+    let layer_id = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("traffic light tooltips"));
+
+    for TrafficLightData(light) in light_q.iter() {
+        egui::containers::show_tooltip(ctx, layer_id, base_id.with(light.traffic_light_id), |ui| {
+            ui.heading(format!("Traffic Light {}", light.traffic_light_id));
+
+            ui.label(format!(
+                "state at t={}: {:?}",
+                ts.time_step,
+                state_at(light, ts.time_step)
+            ));
+            if light.direction.is_some() {
+                ui.label(format!("direction: {:?}", light.direction()));
+            }
+            ui.label(format!("time offset: {}", light.time_offset.unwrap_or(0)));
+
+            let cycle = light
+                .cycle_elements
+                .iter()
+                .map(|e| format!("{:?} ({})", e.color(), e.duration))
+                .collect::<Vec<_>>()
+                .join(" \u{2192} ");
+            ui.label(format!("cycle: {}", cycle));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Red for 3 time steps, red-yellow for 1, green for 4 and yellow for 2, starting at time step 5
+    fn light() -> commonroad_pb::TrafficLight {
+        let element = |duration, color: TrafficLightState| commonroad_pb::CycleElement {
+            duration,
+            color: color as i32,
+        };
+
+        commonroad_pb::TrafficLight {
+            traffic_light_id: 1,
+            cycle_elements: vec![
+                element(3, TrafficLightState::Red),
+                element(1, TrafficLightState::RedYellow),
+                element(4, TrafficLightState::Green),
+                element(2, TrafficLightState::Yellow),
+            ],
+            time_offset: Some(5),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn follows_cycle_from_time_offset() {
+        let light = light();
+
+        let states: Vec<_> = (5..15).map(|ts| state_at(&light, ts)).collect();
+        assert_eq!(
+            states,
+            [
+                TrafficLightState::Red,
+                TrafficLightState::Red,
+                TrafficLightState::Red,
+                TrafficLightState::RedYellow,
+                TrafficLightState::Green,
+                TrafficLightState::Green,
+                TrafficLightState::Green,
+                TrafficLightState::Green,
+                TrafficLightState::Yellow,
+                TrafficLightState::Yellow,
+            ]
+        );
+    }
+
+    #[test]
+    fn wraps_around_after_cycle() {
+        let light = light();
+
+        assert_eq!(state_at(&light, 15), TrafficLightState::Red);
+        assert_eq!(state_at(&light, 18), TrafficLightState::RedYellow);
+        assert_eq!(state_at(&light, 1005), TrafficLightState::Red);
+    }
+
+    #[test]
+    fn repeats_cycle_before_time_offset() {
+        let light = light();
+
+        assert_eq!(state_at(&light, 4), TrafficLightState::Yellow);
+        assert_eq!(state_at(&light, 3), TrafficLightState::Yellow);
+        assert_eq!(state_at(&light, 2), TrafficLightState::Green);
+        assert_eq!(state_at(&light, -2), TrafficLightState::RedYellow);
+        assert_eq!(state_at(&light, -6), TrafficLightState::Yellow);
+    }
+
+    #[test]
+    fn starts_at_zero_without_time_offset() {
+        let light = commonroad_pb::TrafficLight {
+            time_offset: None,
+            ..light()
+        };
+
+        assert_eq!(state_at(&light, 0), TrafficLightState::Red);
+        assert_eq!(state_at(&light, 3), TrafficLightState::RedYellow);
+    }
+
+    #[test]
+    fn inactive_light() {
+        let light = commonroad_pb::TrafficLight {
+            active: Some(false),
+            ..light()
+        };
+        assert_eq!(state_at(&light, 5), TrafficLightState::Inactive);
+
+        let light = commonroad_pb::TrafficLight {
+            active: Some(true),
+            ..light()
+        };
+        assert_eq!(state_at(&light, 5), TrafficLightState::Red);
+    }
+
+    #[test]
+    fn empty_cycle_is_inactive() {
+        let light = commonroad_pb::TrafficLight {
+            cycle_elements: vec![],
+            ..light()
+        };
+        assert_eq!(state_at(&light, 5), TrafficLightState::Inactive);
+
+        let light = commonroad_pb::TrafficLight {
+            cycle_elements: vec![commonroad_pb::CycleElement {
+                duration: 0,
+                color: TrafficLightState::Green as i32,
+            }],
+            ..light()
+        };
+        assert_eq!(state_at(&light, 5), TrafficLightState::Inactive);
+    }
+}
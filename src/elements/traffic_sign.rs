@@ -0,0 +1,376 @@
+use backends::raycast::RaycastPickable;
+use bevy::prelude::*;
+
+use bevy_mod_picking::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use bevy_prototype_lyon::prelude::*;
+
+use crate::commonroad_pb::{self, traffic_sign_element::TrafficSignElementId};
+
+/// Above the traffic lights, but below the occupancies
+const TRAFFIC_SIGN_Z: f32 = 2.6;
+
+const GLYPH_RADIUS: f32 = 1.2;
+
+/// Labels are hidden once a glyph is smaller than this on screen
+const MIN_LABEL_GLYPH_PIXELS: f32 = 6.0;
+
+#[derive(Component)]
+pub struct TrafficSignData {
+    sign: commonroad_pb::TrafficSign,
+    label: String,
+    /// Lanelets referring to the sign, either directly or through their stop line
+    lanelets: Vec<u32>,
+}
+
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub struct HoveredTrafficSign;
+
+/// Distinct speed limits of all traffic signs in the scenario.
+#[derive(Resource, Default)]
+pub(crate) struct SpeedLimits(pub(crate) Vec<SpeedLimit>);
+
+pub(crate) struct SpeedLimit {
+    /// In m/s
+    pub(crate) limit: f64,
+    /// Lanelets referring to any sign with this limit
+    pub(crate) lanelets: Vec<u32>,
+}
+
+impl SpeedLimit {
+    /// Name of the limit in plots.
+    pub(crate) fn label(&self) -> String {
+        if self.lanelets.is_empty() {
+            return format!("scenario speed limit {:.1} m/s", self.limit);
+        }
+
+        let lanelets: Vec<String> = self.lanelets.iter().map(u32::to_string).collect();
+        format!("speed limit {:.1} m/s on lanelets {}", self.limit, lanelets.join(", "))
+    }
+}
+
+/// Rough category of a sign, deciding the glyph it is drawn as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SignGlyph {
+    Prohibition,
+    Mandatory,
+    Warning,
+    Yield,
+    Stop,
+    Priority,
+    Information,
+}
+
+/// Country-independent name of a sign element, like `MAX_SPEED`.
+pub(crate) fn element_name(element: &commonroad_pb::TrafficSignElement) -> Option<&'static str> {
+    use commonroad_pb::*;
+
+    macro_rules! name {
+        ($id:ty, $value:expr) => {
+            <$id>::try_from(*$value).ok().map(|id| id.as_str_name())
+        };
+    }
+
+    match element.traffic_sign_element_id.as_ref()? {
+        TrafficSignElementId::GermanyElementId(v) => name!(traffic_sign_id_germany_enum::TrafficSignIdGermany, v),
+        TrafficSignElementId::ZamundaElementId(v) => name!(traffic_sign_id_zamunda_enum::TrafficSignIdZamunda, v),
+        TrafficSignElementId::UsaElementId(v) => name!(traffic_sign_id_usa_enum::TrafficSignIdUsa, v),
+        TrafficSignElementId::ChinaElementId(v) => name!(traffic_sign_id_china_enum::TrafficSignIdChina, v),
+        TrafficSignElementId::SpainElementId(v) => name!(traffic_sign_id_spain_enum::TrafficSignIdSpain, v),
+        TrafficSignElementId::RussiaElementId(v) => name!(traffic_sign_id_russia_enum::TrafficSignIdRussia, v),
+        TrafficSignElementId::ArgentinaElementId(v) => {
+            name!(traffic_sign_id_argentina_enum::TrafficSignIdArgentina, v)
+        }
+        TrafficSignElementId::BelgiumElementId(v) => name!(traffic_sign_id_belgium_enum::TrafficSignIdBelgium, v),
+        TrafficSignElementId::FranceElementId(v) => name!(traffic_sign_id_france_enum::TrafficSignIdFrance, v),
+        TrafficSignElementId::GreeceElementId(v) => name!(traffic_sign_id_greece_enum::TrafficSignIdGreece, v),
+        TrafficSignElementId::CroatiaElementId(v) => name!(traffic_sign_id_croatia_enum::TrafficSignIdCroatia, v),
+        TrafficSignElementId::ItalyElementId(v) => name!(traffic_sign_id_italy_enum::TrafficSignIdItaly, v),
+        TrafficSignElementId::PuertoRicoElementId(v) => {
+            name!(traffic_sign_id_puerto_rico_enum::TrafficSignIdPuertoRico, v)
+        }
+    }
+}
+
+/// Speed of a speed sign element as shown on the sign, rounded, with its unit and in m/s.
+///
+/// The additional value of speed signs is given in m/s, the signs show km/h or mph.
+fn sign_speed(element: &commonroad_pb::TrafficSignElement) -> Option<(f64, &'static str, f64)> {
+    let name = element_name(element)?;
+    if !name.starts_with("MAX_SPEED") && !name.starts_with("MIN_SPEED") {
+        return None;
+    }
+
+    let speed: f64 = element.additional_values.first()?.parse().ok()?;
+    let (factor, unit) = match element.traffic_sign_element_id.as_ref()? {
+        TrafficSignElementId::UsaElementId(_) | TrafficSignElementId::PuertoRicoElementId(_) => {
+            (3.6 / 1.609_344, "mph")
+        }
+        _ => (3.6, "km/h"),
+    };
+
+    Some(((speed * factor).round(), unit, speed))
+}
+
+/// Readable label of a sign element, like "max speed 50".
+fn element_label(element: &commonroad_pb::TrafficSignElement) -> String {
+    let name = element_name(element)
+        .map(|name| name.to_lowercase().replace('_', " "))
+        .unwrap_or_else(|| "unknown sign".to_owned());
+
+    let mut values = element.additional_values.clone();
+    if let (Some((speed, ..)), Some(value)) = (sign_speed(element), values.first_mut()) {
+        *value = speed.to_string();
+    }
+
+    std::iter::once(name).chain(values).collect::<Vec<_>>().join(" ")
+}
+
+fn glyph(name: &str) -> SignGlyph {
+    if name.starts_with("WARNING") {
+        SignGlyph::Warning
+    } else if name == "YIELD" {
+        SignGlyph::Yield
+    } else if name == "STOP" {
+        SignGlyph::Stop
+    } else if name.contains("PRIORITY") || name == "RIGHT_OF_WAY" {
+        SignGlyph::Priority
+    } else if name.starts_with("MAX_") || name.starts_with("BAN_") || name.starts_with("NO_") {
+        SignGlyph::Prohibition
+    } else if name == "MIN_SPEED"
+        || name.starts_with("PRESCRIBED")
+        || name.starts_with("TURN")
+        || name.starts_with("KEEP")
+        || name == "ROUNDABOUT"
+    {
+        SignGlyph::Mandatory
+    } else {
+        SignGlyph::Information
+    }
+}
+
+fn regular_polygon(sides: usize, radius: f32, rotation: f32) -> shapes::Polygon {
+    shapes::Polygon {
+        points: (0..sides)
+            .map(|i| Vec2::from_angle(rotation + i as f32 * std::f32::consts::TAU / sides as f32) * radius)
+            .collect(),
+        closed: true,
+    }
+}
+
+fn glyph_bundle(glyph: SignGlyph) -> (Path, Fill, Stroke) {
+    use bevy::color::palettes::css;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    let circle = || {
+        GeometryBuilder::build_as(&shapes::Circle {
+            radius: GLYPH_RADIUS,
+            center: Vec2::ZERO,
+        })
+    };
+    let stroke_width = GLYPH_RADIUS * 0.2;
+
+    match glyph {
+        SignGlyph::Prohibition => (circle(), Fill::color(css::WHITE), Stroke::new(css::RED, stroke_width)),
+        SignGlyph::Mandatory => (circle(), Fill::color(css::ROYAL_BLUE), Stroke::new(css::WHITE, stroke_width)),
+        SignGlyph::Warning => (
+            GeometryBuilder::build_as(&regular_polygon(3, GLYPH_RADIUS, FRAC_PI_2)),
+            Fill::color(css::WHITE),
+            Stroke::new(css::RED, stroke_width),
+        ),
+        SignGlyph::Yield => (
+            GeometryBuilder::build_as(&regular_polygon(3, GLYPH_RADIUS, -FRAC_PI_2)),
+            Fill::color(css::WHITE),
+            Stroke::new(css::RED, stroke_width),
+        ),
+        SignGlyph::Stop => (
+            GeometryBuilder::build_as(&regular_polygon(8, GLYPH_RADIUS, PI / 8.0)),
+            Fill::color(css::RED),
+            Stroke::new(css::WHITE, stroke_width),
+        ),
+        SignGlyph::Priority => (
+            GeometryBuilder::build_as(&regular_polygon(4, GLYPH_RADIUS, 0.0)),
+            Fill::color(css::GOLD),
+            Stroke::new(css::WHITE, stroke_width),
+        ),
+        SignGlyph::Information => (
+            GeometryBuilder::build_as(&regular_polygon(4, GLYPH_RADIUS, FRAC_PI_4)),
+            Fill::color(css::ROYAL_BLUE),
+            Stroke::new(css::WHITE, stroke_width),
+        ),
+    }
+}
+
+fn referring_lanelets(cr: &commonroad_pb::CommonRoad, id: u32) -> Vec<u32> {
+    cr.lanelets
+        .iter()
+        .filter(|lanelet| {
+            lanelet.traffic_sign_refs.contains(&id)
+                || lanelet
+                    .stop_line
+                    .as_ref()
+                    .is_some_and(|stop_line| stop_line.traffic_sign_refs.contains(&id))
+        })
+        .map(|lanelet| lanelet.lanelet_id)
+        .collect()
+}
+
+fn spawn_traffic_sign(commands: &mut Commands, cr: &commonroad_pb::CommonRoad, sign: &commonroad_pb::TrafficSign) {
+    // Virtual signs only exist to assign rules to lanelets and are not visible
+    if sign.r#virtual == Some(true) {
+        return;
+    }
+
+    let Some(position) = &sign.position else {
+        bevy::log::warn!("traffic sign {} has no position", sign.traffic_sign_id);
+        return;
+    };
+
+    let label = sign
+        .traffic_sign_elements
+        .iter()
+        .map(element_label)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let sign_glyph = sign
+        .traffic_sign_elements
+        .first()
+        .and_then(element_name)
+        .map_or(SignGlyph::Information, glyph);
+    let (path, fill, stroke) = glyph_bundle(sign_glyph);
+
+    commands.spawn((
+        Name::new(format!("traffic sign {}", sign.traffic_sign_id)),
+        super::SceneEntity,
+        TrafficSignData {
+            sign: sign.to_owned(),
+            label,
+            lanelets: referring_lanelets(cr, sign.traffic_sign_id),
+        },
+        ShapeBundle {
+            path,
+            spatial: SpatialBundle {
+                transform: Transform::from_translation(Vec2::from(position).extend(TRAFFIC_SIGN_Z)),
+                ..default()
+            },
+            ..default()
+        },
+        fill,
+        stroke,
+        PickableBundle::default(),
+        RaycastPickable,
+        On::<Pointer<Over>>::target_insert(HoveredTrafficSign),
+        On::<Pointer<Out>>::target_remove::<HoveredTrafficSign>(),
+    ));
+}
+
+pub fn spawn_traffic_signs(mut commands: Commands, cr: Res<crate::CommonRoad>) {
+    let mut speed_limits: Vec<SpeedLimit> = vec![];
+
+    for sign in &cr.traffic_signs {
+        spawn_traffic_sign(&mut commands, &cr, sign);
+
+        for element in &sign.traffic_sign_elements {
+            if element_name(element) != Some("MAX_SPEED") {
+                continue;
+            }
+
+            // Speed limits are given in m/s
+            let limit = match element.additional_values.first().map(|v| v.parse::<f64>()) {
+                Some(Ok(limit)) => limit,
+                _ => {
+                    bevy::log::warn!("traffic sign {} has an invalid speed limit", sign.traffic_sign_id);
+                    continue;
+                }
+            };
+
+            let lanelets = referring_lanelets(&cr, sign.traffic_sign_id);
+            match speed_limits.iter_mut().find(|speed_limit| speed_limit.limit == limit) {
+                Some(speed_limit) => speed_limit.lanelets.extend(lanelets),
+                None => speed_limits.push(SpeedLimit { limit, lanelets }),
+            }
+        }
+    }
+
+    speed_limits.sort_by(|a, b| a.limit.total_cmp(&b.limit));
+    for speed_limit in speed_limits.iter_mut() {
+        speed_limit.lanelets.sort_unstable();
+        speed_limit.lanelets.dedup();
+    }
+
+    commands.insert_resource(SpeedLimits(speed_limits));
+}
+
+/// Draws the labels of the traffic signs next to their glyphs.
+pub fn traffic_sign_labels(
+    mut contexts: EguiContexts,
+    camera_q: Query<(&Camera, &GlobalTransform), With<crate::MainCamera>>,
+
+    sign_q: Query<(&TrafficSignData, &GlobalTransform)>,
+) {
+    let Ok((camera, camera_transform)) = camera_q.get_single() else {
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+
+    for (data, transform) in sign_q.iter() {
+        let center = transform.translation();
+        let Some(pos) = camera.world_to_viewport(camera_transform, center) else {
+            continue;
+        };
+        let Some(edge) = camera.world_to_viewport(camera_transform, center + Vec3::Y * GLYPH_RADIUS) else {
+            continue;
+        };
+
+        let glyph_pixels = pos.distance(edge);
+        if glyph_pixels < MIN_LABEL_GLYPH_PIXELS {
+            continue;
+        }
+
+        painter.text(
+            egui::pos2(pos.x, pos.y + glyph_pixels + 2.0),
+            egui::Align2::CENTER_TOP,
+            &data.label,
+            egui::FontId::proportional(12.0),
+            egui::Color32::WHITE,
+        );
+    }
+}
+
+pub fn traffic_sign_tooltip(
+    mut contexts: EguiContexts,
+
+    sign_q: Query<&TrafficSignData, With<HoveredTrafficSign>>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    let base_id = egui::Id::new("traffic sign tooltip");
+    let layer_id = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("traffic sign tooltips"));
+
+    for data in sign_q.iter() {
+        let sign = &data.sign;
+
+        egui::containers::show_tooltip(ctx, layer_id, base_id.with(sign.traffic_sign_id), |ui| {
+            ui.heading(format!("Traffic Sign {}", sign.traffic_sign_id));
+
+            for element in &sign.traffic_sign_elements {
+                match sign_speed(element) {
+                    Some((_speed, unit, speed_mps)) => {
+                        ui.label(format!("{} {} ({:.2} m/s)", element_label(element), unit, speed_mps))
+                    }
+                    None => ui.label(element_label(element)),
+                };
+            }
+
+            ui.separator();
+            ui.label(format!("applies to lanelets: {:?}", data.lanelets));
+            ui.label(format!("first occurrences: {:?}", sign.first_occurrences));
+        });
+    }
+}
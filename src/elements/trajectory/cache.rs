@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use crate::global_settings::{GlobalSettings, TimeStep};
+use crate::loading::{LoadDiagnostics, LoadError};
+
+use super::schema::TrajectorySchema;
+use super::{MainTrajectory, MaxCosts, TrajectoryLog, VehicleParams};
+
+/// Number of time steps before and after the current one that are loaded in advance
+const PREFETCH_TIME_STEPS: i32 = 3;
+
+/// Trajectory groups further away from the current time step are despawned
+const EVICT_DISTANCE: i32 = 20;
+
+type LoadTask = Task<Result<Vec<TrajectoryLog>, LoadError>>;
+
+/// Trajectories from the `trajectories.db`, loaded on demand for the time steps around the current one.
+#[derive(Resource)]
+pub(crate) struct TrajectoryCache {
+    db_path: std::path::PathBuf,
+    schema: TrajectorySchema,
+    /// Time steps with trajectories, `None` if the database is empty
+    time_steps: Option<std::ops::RangeInclusive<i32>>,
+    /// Spawned trajectory groups
+    groups: BTreeMap<i32, Entity>,
+    pending: BTreeMap<i32, LoadTask>,
+    /// Time steps without trajectories, loaded again on the next request
+    empty: BTreeSet<i32>,
+    /// Time steps that failed to load, they are only reported once and not loaded again
+    failed: BTreeSet<i32>,
+}
+
+pub(crate) fn open_db(db_path: &std::path::Path) -> rusqlite::Result<rusqlite::Connection> {
+    rusqlite::Connection::open_with_flags(
+        db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX
+    )
+}
+
+fn read_trajectories(
+    conn: &rusqlite::Connection,
+    schema: &TrajectorySchema,
+    time_step: i32,
+) -> Result<Vec<TrajectoryLog>, LoadError> {
+    let mut stmt = conn.prepare(&schema.select_query())?;
+
+    let trajectories = stmt
+        .query_map([time_step], |row| schema.read_trajectory(row))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    Ok(trajectories)
+}
+
+impl TrajectoryCache {
+    pub(crate) fn new(
+        conn: &rusqlite::Connection,
+        db_path: std::path::PathBuf,
+        schema: TrajectorySchema,
+    ) -> Result<Self, LoadError> {
+        let (first, last): (Option<i32>, Option<i32>) = conn.query_row(
+            "SELECT min(time_step), max(time_step) FROM trajectories",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let time_steps = first.zip(last).map(|(first, last)| first..=last);
+        match &time_steps {
+            Some(time_steps) => bevy::log::info!("trajectories available for time steps {:?}", time_steps),
+            None => bevy::log::warn!("trajectories.db does not contain any trajectories"),
+        }
+
+        Ok(Self {
+            db_path,
+            schema,
+            time_steps,
+            groups: BTreeMap::new(),
+            pending: BTreeMap::new(),
+            empty: BTreeSet::new(),
+            failed: BTreeSet::new(),
+        })
+    }
+
+    fn needs_loading(&self, time_step: i32) -> bool {
+        let available = self
+            .time_steps
+            .as_ref()
+            .is_some_and(|time_steps| time_steps.contains(&time_step));
+
+        available
+            && !self.groups.contains_key(&time_step)
+            && !self.pending.contains_key(&time_step)
+            && !self.empty.contains(&time_step)
+            && !self.failed.contains(&time_step)
+    }
+
+    pub(crate) fn db_path(&self) -> &std::path::Path {
+        &self.db_path
+    }
+
+    pub(crate) fn schema(&self) -> &TrajectorySchema {
+        &self.schema
+    }
+
+    pub(crate) fn newest_time_step(&self) -> Option<i32> {
+        self.time_steps.as_ref().map(|time_steps| *time_steps.end())
+    }
+
+    /// Makes the time steps up to `newest` available once a running planner wrote them.
+    ///
+    /// The previously newest time step may have been loaded while the planner was still writing it,
+    /// so it is loaded again.
+    pub(crate) fn extend_time_steps(&mut self, commands: &mut Commands, newest: i32) {
+        let first = match self.time_steps.clone() {
+            Some(time_steps) if newest <= *time_steps.end() => return,
+            Some(time_steps) => {
+                self.unload(commands, *time_steps.end());
+                *time_steps.start()
+            }
+            None => newest,
+        };
+
+        bevy::log::debug!("trajectories available up to ts={}", newest);
+        self.time_steps = Some(first..=newest);
+        // The planner may have written what was missing before
+        self.empty.clear();
+    }
+
+    fn unload(&mut self, commands: &mut Commands, time_step: i32) {
+        if let Some(entity) = self.groups.remove(&time_step) {
+            commands.entity(entity).despawn_recursive();
+        }
+        // Dropping a task cancels it
+        self.pending.remove(&time_step);
+        self.empty.remove(&time_step);
+        self.failed.remove(&time_step);
+    }
+
+    /// Whether the trajectory group of the time step was spawned or will not be until the next request.
+    pub(crate) fn is_loaded(&self, time_step: i32) -> bool {
+        !self.needs_loading(time_step) && !self.pending.contains_key(&time_step)
+    }
+
+    /// Despawns all trajectory groups, the ones around the current time step are loaded again.
+    pub(crate) fn despawn_groups(&mut self, commands: &mut Commands) {
+        for (_ts, entity) in std::mem::take(&mut self.groups) {
+            commands.entity(entity).despawn_recursive();
+        }
+        self.pending.clear();
+        self.empty.clear();
+    }
+
+    fn load(&mut self, time_step: i32) {
+        let db_path = self.db_path.clone();
+        let schema = self.schema.clone();
+
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            bevy::log::debug!("loading trajectories for ts={}", time_step);
+
+            let conn = open_db(&db_path)?;
+            read_trajectories(&conn, &schema, time_step)
+        });
+
+        self.pending.insert(time_step, task);
+    }
+}
+
+/// Starts loading the trajectories around the current time step and despawns far away groups.
+pub(crate) fn request_trajectory_groups(
+    mut commands: Commands,
+    mut cache: ResMut<TrajectoryCache>,
+    time_step: Res<TimeStep>,
+) {
+    // A new cache or new time steps of a running planner may require loading as well
+    if !time_step.is_changed() && !cache.is_changed() {
+        return;
+    }
+
+    if time_step.is_changed() {
+        cache.empty.clear();
+    }
+
+    let current = time_step.time_step;
+
+    for distance in 0..=PREFETCH_TIME_STEPS {
+        for ts in [current - distance, current + distance] {
+            if cache.needs_loading(ts) {
+                cache.load(ts);
+            }
+        }
+    }
+
+    let far_away = |ts: &i32| (ts - current).abs() > EVICT_DISTANCE;
+
+    // Dropping a task cancels it
+    cache.pending.retain(|ts, _task| !far_away(ts));
+
+    let evicted: Vec<i32> = cache.groups.keys().copied().filter(far_away).collect();
+    for ts in evicted {
+        if let Some(entity) = cache.groups.remove(&ts) {
+            bevy::log::debug!("evicting trajectory group ts={}", ts);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Spawns the trajectory groups of all finished loading tasks.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_loaded_trajectory_groups(
+    mut commands: Commands,
+    mut cache: ResMut<TrajectoryCache>,
+    mut diagnostics: ResMut<LoadDiagnostics>,
+
+    max_costs: Res<MaxCosts>,
+    time_step: Res<TimeStep>,
+    settings: Res<GlobalSettings>,
+
+    mtraj: Option<Res<MainTrajectory>>,
+    vparams: Option<Res<VehicleParams>>,
+
+    mut redraw_request_events: EventWriter<bevy::window::RequestRedraw>,
+) {
+    // Finished tasks do not wake up the event loop, so keep polling until all groups are spawned
+    if !cache.pending.is_empty() {
+        redraw_request_events.send(bevy::window::RequestRedraw);
+    }
+
+    let finished: Vec<i32> = cache
+        .pending
+        .iter()
+        .filter(|(_ts, task)| task.is_finished())
+        .map(|(ts, _task)| *ts)
+        .collect();
+
+    for ts in finished {
+        let Some(task) = cache.pending.remove(&ts) else {
+            continue;
+        };
+
+        let result = bevy::tasks::block_on(task);
+        let Some(trajectories) = diagnostics.ok(&format!("trajectories for time step {}", ts), result) else {
+            cache.failed.insert(ts);
+            continue;
+        };
+
+        if trajectories.is_empty() {
+            cache.empty.insert(ts);
+            continue;
+        }
+
+        let ego = mtraj.as_deref().zip(vparams.as_deref());
+        let group = super::spawn_trajectory_group(
+            &mut commands,
+            ts,
+            &trajectories,
+            ts == time_step.time_step,
+            settings.show_infeasible,
+            max_costs.max_costs,
+            ego,
+        );
+
+        cache.groups.insert(ts, group);
+    }
+}
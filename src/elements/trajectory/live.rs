@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use crate::global_settings::{CurrentTimeStep, GlobalSettings};
+use crate::loading::LoadError;
+
+use super::cache::TrajectoryCache;
+use super::schema::TrajectorySchema;
+use super::{MainTrajectory, MainTrajectoryPath, MaxCosts, VehicleParams};
+
+/// Interval in which the logs of a running planner are checked for new data
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Follows a planner that is still writing its logs.
+#[derive(Resource)]
+pub(crate) struct LiveLogs {
+    logs: std::path::PathBuf,
+    timer: Timer,
+    /// Size of `logs.csv` when it was last read
+    main_log_size: u64,
+    task: Option<LiveTask>,
+}
+
+enum LiveTask {
+    /// The planner may not have created the trajectories database yet when starting
+    Open(Task<Result<LiveOpen, LoadError>>),
+    Poll(Task<Result<LivePoll, LoadError>>),
+}
+
+/// Trajectories database, once the planner created it.
+struct LiveOpen {
+    vehicle_params: Option<VehicleParams>,
+    max_costs: MaxCosts,
+    trajectory_cache: TrajectoryCache,
+}
+
+/// New data found in the logs since the last poll.
+struct LivePoll {
+    newest_time_step: Option<i32>,
+    max_costs: Option<f64>,
+    main_log_size: u64,
+    main_trajectory: Option<MainTrajectory>,
+}
+
+impl LiveLogs {
+    pub(crate) fn new(logs: std::path::PathBuf) -> Self {
+        Self {
+            logs,
+            timer: Timer::new(POLL_INTERVAL, TimerMode::Repeating),
+            main_log_size: 0,
+            task: None,
+        }
+    }
+}
+
+fn open_logs(logs: &std::path::Path) -> Result<LiveOpen, LoadError> {
+    let db_path = logs.join("trajectories.db");
+    let conn = super::cache::open_db(&db_path)?;
+
+    // Progress is only shown for the initial loading
+    let progress = crate::loading::LoadingProgress::default();
+    let (max_costs, trajectory_cache) = super::open_trajectories(&conn, &db_path, &progress)?;
+
+    let vehicle_params = super::read_vehicle_params(&conn)
+        .inspect_err(|err| bevy::log::warn!("could not read the vehicle parameters: {}", err))
+        .ok();
+
+    Ok(LiveOpen {
+        vehicle_params,
+        max_costs,
+        trajectory_cache,
+    })
+}
+
+fn poll_logs(
+    logs: &std::path::Path,
+    db_path: &std::path::Path,
+    schema: &TrajectorySchema,
+    known_time_step: Option<i32>,
+    main_log_size: u64,
+) -> Result<LivePoll, LoadError> {
+    use rusqlite::OptionalExtension;
+
+    let conn = super::cache::open_db(db_path)?;
+
+    // The planner appends the time steps in order, so the last row belongs to the newest one
+    let newest_time_step: Option<i32> = conn
+        .query_row(
+            "SELECT time_step FROM trajectories ORDER BY rowid DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let max_costs = match newest_time_step {
+        // The previously newest time step may have been incomplete when it was last read
+        Some(newest) if Some(newest) > known_time_step => conn.query_row(
+            &format!(
+                "SELECT max(costs_cumulative_weighted) FROM {} \
+                 WHERE time_step >= ?1 AND costs_cumulative_weighted BETWEEN -1e308 AND 1e308",
+                schema.costs_table()
+            ),
+            [known_time_step.unwrap_or(i32::MIN)],
+            |row| row.get(0),
+        )?,
+        _ => None,
+    };
+
+    let main_log_path = logs.join("logs.csv");
+    let size = std::fs::metadata(&main_log_path).map_err(LoadError::io(&main_log_path))?.len();
+
+    let main_trajectory = if size != main_log_size {
+        // Progress is only shown for the initial loading
+        let progress = crate::loading::LoadingProgress::default();
+        let main_trajectories = super::log::read_main_log(&main_log_path, &progress)?;
+
+        Some(super::make_main_trajectory(&main_trajectories)?)
+    } else {
+        None
+    };
+
+    Ok(LivePoll {
+        newest_time_step,
+        max_costs,
+        main_log_size: size,
+        main_trajectory,
+    })
+}
+
+/// Regularly checks the logs for new time steps and adds them to the scene.
+///
+/// Until the planner created the trajectories database, opening it is retried instead.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn poll_live_logs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut live: ResMut<LiveLogs>,
+    cache: Option<ResMut<TrajectoryCache>>,
+    max_costs: Option<ResMut<MaxCosts>>,
+    mut cts: ResMut<CurrentTimeStep>,
+    settings: Res<GlobalSettings>,
+
+    main_path_q: Query<Entity, With<MainTrajectoryPath>>,
+) {
+    live.timer.tick(time.delta());
+
+    let Some(task) = &mut live.task else {
+        if live.timer.just_finished() {
+            let logs = live.logs.clone();
+
+            let task = match &cache {
+                Some(cache) => {
+                    let db_path = cache.db_path().to_owned();
+                    let schema = cache.schema().clone();
+                    let known_time_step = cache.newest_time_step();
+                    let main_log_size = live.main_log_size;
+
+                    LiveTask::Poll(AsyncComputeTaskPool::get().spawn(async move {
+                        poll_logs(&logs, &db_path, &schema, known_time_step, main_log_size)
+                    }))
+                }
+                None => LiveTask::Open(AsyncComputeTaskPool::get().spawn(async move { open_logs(&logs) })),
+            };
+            live.task = Some(task);
+        }
+        return;
+    };
+
+    let result = match task {
+        LiveTask::Open(task) => {
+            let Some(result) = bevy::tasks::block_on(bevy::tasks::poll_once(task)) else {
+                return;
+            };
+            live.task = None;
+
+            match result {
+                Ok(open) => {
+                    bevy::log::info!("opened the trajectories database of the running planner");
+
+                    if let Some(vehicle_params) = open.vehicle_params {
+                        commands.insert_resource(vehicle_params);
+                    }
+                    commands.insert_resource(open.max_costs);
+                    commands.insert_resource(open.trajectory_cache);
+                }
+                Err(err) => {
+                    bevy::log::debug!("could not open the trajectories database of the running planner: {}", err);
+                }
+            }
+            return;
+        }
+        LiveTask::Poll(task) => {
+            let Some(result) = bevy::tasks::block_on(bevy::tasks::poll_once(task)) else {
+                return;
+            };
+            result
+        }
+    };
+    live.task = None;
+
+    let (Some(mut cache), Some(mut max_costs)) = (cache, max_costs) else {
+        return;
+    };
+
+    // The planner may be in the middle of writing, so errors are only temporary
+    let poll = match result {
+        Ok(poll) => poll,
+        Err(err) => {
+            bevy::log::debug!("could not poll the logs of the running planner: {}", err);
+            return;
+        }
+    };
+
+    if let Some(new_max_costs) = poll.max_costs {
+        if new_max_costs > max_costs.max_costs {
+            max_costs.max_costs = new_max_costs;
+        }
+    }
+
+    if let Some(newest) = poll.newest_time_step {
+        if Some(newest) > cache.newest_time_step() {
+            cache.extend_time_steps(&mut commands, newest);
+
+            let newest = newest as f32;
+            if newest > *cts.prediction_range.end() {
+                cts.prediction_range = *cts.prediction_range.start()..=newest;
+            }
+            if settings.follow_newest_time_step {
+                cts.dynamic_time_step = newest;
+            }
+        }
+    }
+
+    live.main_log_size = poll.main_log_size;
+
+    if let Some(mtraj) = poll.main_trajectory {
+        for entity in main_path_q.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        commands.spawn(super::make_main_trajectory_bundle(&mtraj));
+        commands.insert_resource(mtraj);
+    }
+}
@@ -2,6 +2,8 @@ use bevy::prelude::*;
 
 use bevy_prototype_lyon::prelude::Stroke;
 
+use crate::loading::{LoadError, LoadingProgress};
+
 fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -143,10 +145,9 @@ pub(crate) struct TrajectoryLog {
     #[serde(flatten)]
     pub(crate) costs: std::collections::HashMap<String, f64>,
 
-    pub(crate) inf_kin_yaw_rate: i32,
-    pub(crate) inf_kin_acceleration: i32,
-    pub(crate) inf_kin_max_curvature: i32,
-    pub(crate) inf_kin_max_curvature_rate: i32,
+    /// Infeasibility check results by name, the available checks depend on the planner version
+    #[serde(skip)]
+    pub(crate) infeasibility: std::collections::HashMap<String, f64>,
 }
 
 impl TrajectoryLog {
@@ -240,6 +241,18 @@ impl TrajectoryLog {
             .trajectory_lat_plot_data(Some(self.time_step))
     }
 
+    pub(crate) fn sorted_infeasibility(&self) -> Vec<(&str, f64)> {
+        let mut infeasibility: Vec<_> = self
+            .infeasibility
+            .iter()
+            .map(|(k, v)| (k.as_str(), *v))
+            .collect();
+
+        infeasibility.sort_by(|(k1, _v1), (k2, _v2)| k1.cmp(k2));
+
+        infeasibility
+    }
+
     pub(crate) fn sorted_nonzero_costs<'a>(
         &'a self,
         cost_threshold: Option<f64>,
@@ -327,10 +340,26 @@ pub(crate) fn read_log(
 
 pub(crate) fn read_main_log(
     path: &std::path::Path,
-) -> Result<Vec<MainLog>, Box<dyn std::error::Error>> {
-    let mut rdr = csv::ReaderBuilder::new().delimiter(b';').from_path(path)?;
+    progress: &LoadingProgress,
+) -> Result<Vec<MainLog>, LoadError> {
+    let csv_error = |source| LoadError::Csv {
+        path: path.to_owned(),
+        source,
+    };
 
-    let res = rdr.deserialize().collect::<Result<Vec<_>, _>>()?;
+    let file_size = std::fs::metadata(path).map_err(LoadError::io(path))?.len();
+    progress.start_stage("reading main trajectory", file_size);
+
+    let mut rdr = csv::ReaderBuilder::new().delimiter(b';').from_path(path).map_err(csv_error)?;
+    let headers = rdr.byte_headers().map_err(csv_error)?.clone();
+
+    let mut res = vec![];
+    let mut record = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut record).map_err(csv_error)? {
+        res.push(record.deserialize(Some(&headers)).map_err(csv_error)?);
+
+        progress.set_done(rdr.position().byte());
+    }
 
     Ok(res)
 }
@@ -342,7 +371,11 @@ pub struct MainTrajectory {
     kinematic_data: KinematicData,
 }
 
-pub(crate) fn reassemble_main_trajectory(mtraj: &[MainLog]) -> KinematicData {
+pub(crate) fn reassemble_main_trajectory(mtraj: &[MainLog]) -> Result<KinematicData, LoadError> {
+    let missing_data = |what| {
+        move || LoadError::schema(format!("the main trajectory log has entries without {}", what))
+    };
+
     let x_positions_m = mtraj
         .iter()
         .map(|traj| traj.x_position_vehicle_m)
@@ -356,25 +389,25 @@ pub(crate) fn reassemble_main_trajectory(mtraj: &[MainLog]) -> KinematicData {
         .iter()
         .map(|traj| traj.kinematic_data.velocities_mps.first().copied())
         .collect::<Option<Vec<f32>>>()
-        .unwrap();
+        .ok_or_else(missing_data("velocities"))?;
 
     let accelerations_mps2 = mtraj
         .iter()
         .map(|traj| traj.kinematic_data.accelerations_mps2.first().copied())
         .collect::<Option<Vec<f32>>>()
-        .unwrap();
+        .ok_or_else(missing_data("accelerations"))?;
 
     let theta_orientations_rad = mtraj
         .iter()
         .map(|traj| traj.kinematic_data.theta_orientations_rad.first().copied())
         .collect::<Option<Vec<f32>>>()
-        .unwrap();
+        .ok_or_else(missing_data("orientations"))?;
 
     let kappa_rad = mtraj
         .iter()
         .map(|traj| traj.kinematic_data.kappa_rad.first().copied())
         .collect::<Option<Vec<f32>>>()
-        .unwrap();
+        .ok_or_else(missing_data("curvatures"))?;
 
     let curvilinear_orientations_rad = mtraj
         .iter()
@@ -385,9 +418,9 @@ pub(crate) fn reassemble_main_trajectory(mtraj: &[MainLog]) -> KinematicData {
                 .copied()
         })
         .collect::<Option<Vec<f32>>>()
-        .unwrap();
+        .ok_or_else(missing_data("curvilinear orientations"))?;
 
-    KinematicData {
+    Ok(KinematicData {
         x_positions_m,
         y_positions_m,
         theta_orientations_rad,
@@ -397,5 +430,5 @@ pub(crate) fn reassemble_main_trajectory(mtraj: &[MainLog]) -> KinematicData {
         accelerations_mps2,
         trajectory_long: vec![],
         trajectory_lat: vec![],
-    }
+    })
 }
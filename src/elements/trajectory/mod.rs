@@ -1,7 +1,7 @@
-use std::collections::{BTreeMap, HashMap};
 
 use backends::raycast::RaycastPickable;
 use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
 
 use bevy_mod_picking::prelude::*;
 use bevy_prototype_lyon::prelude::*;
@@ -9,9 +9,18 @@ use bevy_prototype_lyon::prelude::*;
 use bevy_egui::EguiContexts;
 
 use crate::global_settings::{TimeStep, CurrentTimeStep};
+use crate::loading::{AppState, LoadDiagnostics, LoadError, LoadingProgress};
 
 mod plot;
 
+mod schema;
+
+pub(crate) mod cache;
+
+pub(crate) mod watch;
+
+pub(crate) mod live;
+
 pub(crate) mod log;
 
 pub(crate) use log::{KinematicData, MainLog, TrajectoryLog};
@@ -23,6 +32,24 @@ pub struct MainTrajectory {
     kinematic_data: KinematicData,
 }
 
+impl MainTrajectory {
+    /// Time step, position, velocity and orientation of the ego vehicle along the driven path.
+    pub(crate) fn states(&self) -> impl Iterator<Item = (i32, Vec2, f32, f32)> + '_ {
+        let kd = &self.kinematic_data;
+
+        std::iter::zip(
+            self.path.iter(),
+            std::iter::zip(kd.velocities_mps.iter(), kd.theta_orientations_rad.iter()),
+        )
+        .enumerate()
+        .map(|(ts, (&pos, (&velocity, &orientation)))| (ts as i32, pos, velocity, orientation))
+    }
+}
+
+/// Entity showing the path of the [`MainTrajectory`].
+#[derive(Component, Clone, Copy)]
+pub(crate) struct MainTrajectoryPath;
+
 #[derive(Component, Default, Copy, Clone)]
 #[component(storage = "SparseSet")]
 pub struct HoveredTrajectory;
@@ -81,7 +108,7 @@ pub(super) fn update_stroke(
     }
 }
 
-fn make_trajectory_bundle(traj: &TrajectoryLog) -> Option<(impl Bundle, Option<impl Bundle>, f64)> {
+fn make_trajectory_bundle(traj: &TrajectoryLog, max_costs: f64) -> Option<(impl Bundle, Option<impl Bundle>)> {
     let points: Vec<Vec2> = traj.kinematic_data.positions().collect();
 
     if !points.iter().all(|v| v.x.is_finite() && v.y.is_finite()) {
@@ -109,7 +136,7 @@ fn make_trajectory_bundle(traj: &TrajectoryLog) -> Option<(impl Bundle, Option<i
             },
             ..default()
         },
-        traj.normal_stroke(100.0),
+        traj.normal_stroke(max_costs),
         On::<Pointer<Select>>::send_event::<SelectTrajectoryEvent>(),
         // On::<Pointer<Select>>::target_insert((SelectedTrajectory, Stroke::new(selected_color, 0.02))),
         // On::<Pointer<Deselect>>::target_commands_mut(|_ptr, commands| {
@@ -128,7 +155,7 @@ fn make_trajectory_bundle(traj: &TrajectoryLog) -> Option<(impl Bundle, Option<i
         None
     };
 
-    Some((base_bundle, extra_bundle, traj.costs_cumulative_weighted))
+    Some((base_bundle, extra_bundle))
 }
 
 use bevy_polyline::prelude::*;
@@ -218,36 +245,37 @@ pub(super) fn update_selected_trajectory(
     }
 }
 
-fn make_main_trajectory_bundle(main_trajectories: &[MainLog]) -> (MainTrajectory, impl Bundle) {
+fn make_main_trajectory(main_trajectories: &[MainLog]) -> Result<MainTrajectory, LoadError> {
     let mpoints = main_trajectories
         .iter()
         .map(|traj| traj.kinematic_data.positions().next())
         .collect::<Option<Vec<Vec2>>>()
-        .unwrap();
-
-    let traj_shape = crate::extra_shapes::Polyline {
-        points: mpoints.clone(),
-    };
+        .ok_or_else(|| LoadError::schema("the main trajectory log has entries without positions"))?;
 
-    let mtraj = MainTrajectory {
+    Ok(MainTrajectory {
         path: mpoints,
-        kinematic_data: log::reassemble_main_trajectory(main_trajectories),
+        kinematic_data: log::reassemble_main_trajectory(main_trajectories)?,
+    })
+}
+
+fn make_main_trajectory_bundle(mtraj: &MainTrajectory) -> impl Bundle {
+    let traj_shape = crate::extra_shapes::Polyline {
+        points: mtraj.path.clone(),
     };
 
     (
-        mtraj,
-        (
-            Name::new("main trajectory"),
-            ShapeBundle {
-                path: GeometryBuilder::build_as(&traj_shape),
-                spatial: SpatialBundle {
-                    transform: Transform::from_xyz(0.0, 0.0, 0.5),
-                    ..default()
-                },
+        Name::new("main trajectory"),
+        MainTrajectoryPath,
+        super::SceneEntity,
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&traj_shape),
+            spatial: SpatialBundle {
+                transform: Transform::from_xyz(0.0, 0.0, 0.5),
                 ..default()
             },
-            Stroke::new(Color::srgba(0.4, 0.6, 0.18, 0.7), 0.15),
-        ),
+            ..default()
+        },
+        Stroke::new(Color::srgba(0.4, 0.6, 0.18, 0.7), 0.15),
     )
 }
 
@@ -290,340 +318,255 @@ impl VehicleParams {
 
 fn read_vehicle_params(
     conn: &rusqlite::Connection,
-) -> rusqlite::Result<VehicleParams> {
+) -> Result<VehicleParams, LoadError> {
+    use rusqlite::OptionalExtension;
+
     let mut stmt = conn.prepare(
         "SELECT json_extract(value, '$.vehicle') FROM meta WHERE key = 'config_sim'"
-    ).unwrap();
+    )?;
     let vparams: VehicleParams = stmt.query_row([], |row| {
-        let rusqlite::types::ValueRef::Text(st) = row.get_ref(0)? else { todo!() };
+        let st = match row.get_ref(0)? {
+            rusqlite::types::ValueRef::Text(st) => st,
+            rusqlite::types::ValueRef::Null => {
+                return Err(LoadError::schema("the 'config_sim' meta entry has no 'vehicle' key").into_sqlite());
+            }
+            value => {
+                return Err(rusqlite::Error::InvalidColumnType(0, "config_sim".to_owned(), value.data_type()));
+            }
+        };
         let rstr = std::str::from_utf8(st)?;
-        let data: VehicleParams = miniserde::json::from_str(rstr).map_err(|err| {
-            return rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err));
-        })?;
+        let data: VehicleParams = miniserde::json::from_str(rstr)
+            .map_err(|_| LoadError::json("vehicle parameters").into_sqlite())?;
 
         Ok(data)
-    }).unwrap();
+    })
+    .optional()?
+    .ok_or_else(|| LoadError::schema("the meta table has no 'config_sim' entry"))?;
 
     Ok(vparams)
 }
 
-fn read_trajectories(
+fn read_max_costs(
     conn: &rusqlite::Connection,
-) -> rusqlite::Result<Vec<TrajectoryLog>> {
-    let filter_column_names = |stmt: rusqlite::Statement| {
-        stmt
-            .column_names()
-            .into_iter()
-            .filter(|name| *name != "id" && *name != "time_step")
-            .map(ToString::to_string)
-            .collect()
-    };
-
-    let stmt = conn.prepare(
-        "SELECT * FROM infeasability"
-    )?;
-    let inf_names: Vec<_> = filter_column_names(stmt);
-
-    let stmt = conn.prepare(
-        "SELECT * FROM costs"
-    )?;
-    let cost_names = filter_column_names(stmt);
-
-    bevy::log::info!("column names done");
-
-    // let cost_names = stmt
-    //     .column_names()
-    //     .iter()
-    //     .filter(|&name| *name != "id" && *name != "time_step")
-    //     .collect();
-    
-    // conn.execute_batch(
-    //     "ANALYZE trajectories;
-    //     ANALYZE trajectories_meta;
-    //     ANALYZE costs;
-    //     ANALYZE infeasability;
-    //     "
-    // )?;
-
-    let mut stmt = conn.prepare(
-        "SELECT count(*) FROM trajectories"
-    )?;
-    let count: i64 = stmt.query_row([], |row| {
-        row.get(0)
-    })?;
-
-    let mut stmt = conn.prepare(
-        "SELECT *
-        FROM trajectories 
-            INNER JOIN trajectories_meta USING (time_step, id)
-            INNER JOIN costs USING (time_step, id)
-            INNER JOIN infeasability USING (time_step, id)"
+    schema: &schema::TrajectorySchema,
+    progress: &LoadingProgress,
+) -> Result<MaxCosts, LoadError> {
+    // A single aggregate query, so the progress of this stage is unknown
+    progress.start_stage("computing maximum trajectory costs", 0);
+
+    // Only the table containing the costs needs to be scanned
+    let max_costs: Option<f64> = conn.query_row(
+        &format!(
+            "SELECT max(costs_cumulative_weighted) FROM {} WHERE costs_cumulative_weighted BETWEEN -1e308 AND 1e308",
+            schema.costs_table()
+        ),
+        [],
+        |row| row.get(0),
     )?;
-    let names = stmt.column_names();
-    bevy::log::trace!("names={:#?}", names);
 
-    bevy::log::debug!("count={}", count);
-    
-    let mut idx = 1;
+    Ok(MaxCosts {
+        max_costs: max_costs.unwrap_or(f64::MIN_POSITIVE),
+    })
+}
 
-    let ittt = stmt.query_map([], |row| {
-        bevy::log::trace!("{}/{}", idx, count);
-        idx += 1;
+/// Everything read from the logs directory by the background loading task.
+#[derive(Default)]
+struct LoadedLogs {
+    main_trajectory: Option<MainTrajectory>,
+    vehicle_params: Option<VehicleParams>,
+    trajectories: Option<(MaxCosts, cache::TrajectoryCache)>,
+    errors: Vec<(&'static str, LoadError)>,
+}
 
-        let convert = |idx| -> rusqlite::Result<Vec<f32>> {
-            let rusqlite::types::ValueRef::Text(st) = row.get_ref(idx)? else { todo!() };
-            let rstr = std::str::from_utf8(st).map_err(rusqlite::Error::Utf8Error)?;
-            let data: Vec<f32> = miniserde::json::from_str(rstr).unwrap();
-            Ok(data)
-        };
-        let kd = KinematicData {
-            x_positions_m: convert("x")?,
-            y_positions_m: convert("y")?,
-            theta_orientations_rad: convert("theta")?,
-            kappa_rad: convert("kappa")?,
-            curvilinear_orientations_rad: convert("curvilinear_theta")?,
-            velocities_mps: convert("v")?,
-            accelerations_mps2: convert("a")?,
-            trajectory_long: convert("trajectory_long")?,
-            trajectory_lat: convert("trajectory_lat")?,
-        };
+impl LoadedLogs {
+    /// Keeps the error of a failed load so that it can be reported once loading finished.
+    fn ok<T>(&mut self, what: &'static str, result: Result<T, LoadError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push((what, err));
+                None
+            }
+        }
+    }
+}
 
-        let fetch_map = |names: &Vec<String>| -> rusqlite::Result<_> {
-            Ok(names
-                .iter()
-                .map(|name| Ok((name.clone(), row.get(name.as_str())?)) )
-                .collect::<rusqlite::Result<HashMap<_, _>>>()?)
-        };
-        let fetch_map_i32 = |names: &Vec<String>| -> rusqlite::Result<_> {
-            Ok(names
-                .iter()
-                .map(|name| Ok((name.clone(), row.get(name.as_str())?)) )
-                .collect::<rusqlite::Result<HashMap<_, _>>>()?)
-        };
+#[derive(Resource)]
+pub(crate) struct LogsLoadingTask(Task<LoadedLogs>);
 
-        let costs: HashMap<String, f64> = fetch_map(&cost_names)?;
-        let infeasability: HashMap<String, i32> = fetch_map_i32(&inf_names)?;
-
-        let tl = TrajectoryLog {
-            time_step: row.get("time_step")?,
-            // TODO: remove?
-            trajectory_number: -1,
-            unique_id: row.get("id")?,
-            feasible: row.get("feasible")?,
-            horizon: row.get("horizon")?,
-            dt: row.get("dt")?,
-            kinematic_data: kd,
-            s_position_m: row.get("s_position")?,
-            d_position_m: row.get("d_position")?,
-            ego_risk: row.get("ego_risk")?,
-            obst_risk: row.get("obst_risk")?,
-            collision_detected: row.get("collision_detected")?,
-            boundary_harm: row.get("boundary_harm")?,
-            costs_cumulative_weighted: row.get("costs_cumulative_weighted")?,
-            costs,
-            inf_kin_yaw_rate: *infeasability.get("inf_yaw_rate").unwrap(),
-            inf_kin_acceleration: *infeasability.get("inf_acceleration").unwrap(),
-            inf_kin_max_curvature: *infeasability.get("inf_curvature").unwrap(),
-            inf_kin_max_curvature_rate: *infeasability.get("inf_curvature_rate").unwrap(),
-        };
+impl LogsLoadingTask {
+    fn spawn(logs: std::path::PathBuf, progress: LoadingProgress) -> Self {
+        let task = AsyncComputeTaskPool::get().spawn(async move { load_logs(&logs, &progress) });
 
-        Ok(tl)
-    })?;
+        Self(task)
+    }
+}
 
-    ittt.collect()
+/// Removes all data read from the planner logs, a running loading task is cancelled.
+pub(crate) fn remove_logs(commands: &mut Commands) {
+    commands.remove_resource::<LogsLoadingTask>();
+    commands.remove_resource::<watch::MainLogLoadingTask>();
+    commands.remove_resource::<cache::TrajectoryCache>();
+    commands.remove_resource::<MainTrajectory>();
+    commands.remove_resource::<MaxCosts>();
+    commands.remove_resource::<VehicleParams>();
 }
 
-pub fn spawn_trajectories(
+/// Starts loading the planner logs in the background, the scenario is shown in the meantime.
+pub fn start_loading_logs(
     mut commands: Commands,
     args: Res<crate::args::Args>,
-
-    // _polyline_assets: ResMut<Assets<Polyline>>,
-    // mut material_assets: ResMut<Assets<PolylineMaterial>>,
+    progress: Res<LoadingProgress>,
+    mut next_state: ResMut<NextState<AppState>>,
+    event_loop: Option<NonSend<bevy::winit::EventLoopProxy<bevy::winit::WakeUp>>>,
 ) {
-    let main_trajectories_path = std::path::Path::join(&args.logs, "logs.csv");
-    let main_trajectories = match log::read_main_log(&main_trajectories_path) {
-        Ok(trajectories) => trajectories,
-        Err(e) => {
-            bevy::log::error!("could not read trajectory logs (continuing anyway): {}", e);
-            return;
-        },
+    let Some(logs) = args.logs.clone() else {
+        bevy::log::info!("no logs directory given, skipping trajectories");
+        next_state.set(AppState::Running);
+        return;
     };
-    let (mtraj_res, mtraj_bundle) = make_main_trajectory_bundle(&main_trajectories);
-
-    commands.spawn(mtraj_bundle);
-
-    let db_path = std::path::Path::join(&args.logs, "trajectories.db");
-    let conn = rusqlite::Connection::open_with_flags(
-        db_path,
-        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX
-    ).unwrap();
-
-    let vparams = read_vehicle_params(&conn).unwrap();
 
-    commands.insert_resource(vparams.clone());
-
-    let traj = read_trajectories(&conn).unwrap();
-
-    let trajectories_path = std::path::Path::join(&args.logs, "trajectories.csv");
-    let io_pool = bevy::tasks::TaskPoolBuilder::new()
-        .num_threads(8)
-        .thread_name("trajectory builder".to_string())
-        .build();
+    if args.live {
+        // New time steps are appended instead of reloading everything on changes
+        commands.insert_resource(live::LiveLogs::new(logs.clone()));
+    } else {
+        match watch::LogsWatcher::new(&logs, event_loop.map(|event_loop| event_loop.clone())) {
+            Ok(watcher) => commands.insert_resource(watcher),
+            Err(err) => bevy::log::warn!("could not watch {} for changes: {}", logs.display(), err),
+        }
+    }
 
-    let file = std::fs::File::open(trajectories_path).unwrap();
+    commands.insert_resource(LogsLoadingTask::spawn(logs, progress.clone()));
+}
 
-    let map = unsafe { memmap2::MmapOptions::new().populate().map(&file) }.unwrap();
-    let cursor = std::io::Cursor::new(map);
+fn load_logs(
+    logs: &std::path::Path,
+    progress: &LoadingProgress,
+) -> LoadedLogs {
+    let mut loaded = LoadedLogs::default();
 
-    let mut rdr = csv::ReaderBuilder::new()
-        .delimiter(b';')
-        .from_reader(cursor);
+    let main_trajectories_path = logs.join("logs.csv");
+    let mtraj_res = log::read_main_log(&main_trajectories_path, progress)
+        .and_then(|main_trajectories| make_main_trajectory(&main_trajectories));
+    loaded.main_trajectory = loaded.ok("main trajectory", mtraj_res);
 
-    // let mut sr = csv::StringRecord::default();
+    progress.start_stage("opening trajectories database", 0);
 
-    let headers = rdr.headers().unwrap().to_owned();
+    let db_path = logs.join("trajectories.db");
+    let conn = cache::open_db(&db_path).map_err(LoadError::from);
+    let Some(conn) = loaded.ok("trajectories database", conn) else {
+        return loaded;
+    };
 
-    // let rdr = Arc::new(rdr);
+    loaded.vehicle_params = loaded.ok("vehicle parameters", read_vehicle_params(&conn));
 
-    /*
-    let (sender, receiver) = std::sync::mpsc::channel();
-    let buf = std::sync::Arc::new(thingbuf::ThingBuf::<csv::StringRecord>::new(64));
+    let trajectories = open_trajectories(&conn, &db_path, progress);
+    loaded.trajectories = loaded.ok("trajectories", trajectories);
 
-    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    loaded
+}
 
-    type Task = bevy::tasks::Task<Result<(), Box<dyn std::error::Error + Send + Sync>>>;
+/// Trajectories are loaded on demand by the TrajectoryCache, only the maximum costs are read up front.
+fn open_trajectories(
+    conn: &rusqlite::Connection,
+    db_path: &std::path::Path,
+    progress: &LoadingProgress,
+) -> Result<(MaxCosts, cache::TrajectoryCache), LoadError> {
+    let schema = schema::TrajectorySchema::probe(conn)?;
+    let max_costs = read_max_costs(conn, &schema, progress)?;
+    let trajectory_cache = cache::TrajectoryCache::new(conn, db_path.to_owned(), schema)?;
+
+    Ok((max_costs, trajectory_cache))
+}
 
-    let reader_task: Task = io_pool.spawn({
-        let buf = buf.clone();
-        let done = done.clone();
+/// Inserts the planner logs once the background loading task finished.
+pub(crate) fn finish_loading_logs(
+    mut commands: Commands,
+    mut task: ResMut<LogsLoadingTask>,
+    progress: Res<LoadingProgress>,
+    mut diagnostics: ResMut<LoadDiagnostics>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(loaded) = bevy::tasks::block_on(bevy::tasks::poll_once(&mut task.0)) else {
+        return;
+    };
 
-        async move {
-            while !rdr.is_done() {
-                match buf.push_ref() {
-                    Ok(mut bref) => {
-                        rdr.read_record(&mut bref)?;
-                    }
-                    Err(_) => {
-                        bevy::log::debug!("failed to push, sleeping");
-                        std::thread::sleep(std::time::Duration::from_micros(100));
-                        continue;
-                    }
-                };
-            }
-            bevy::log::debug!("finished reading records");
-            done.store(true, std::sync::atomic::Ordering::Relaxed);
-            Ok(())
-        }
-    });
+    commands.remove_resource::<LogsLoadingTask>();
+    progress.finish();
+    next_state.set(AppState::Running);
 
-    let mut task_list: Vec<_> = vec![reader_task];
+    for (what, err) in loaded.errors {
+        diagnostics.warn(what, err);
+    }
 
-    // for _ in 0..6 {
-    //for _ in 0..1 
-    
-    // let h_mat = material_assets.add(PolylineMaterial {
-    //     width: 3.0,
-    //     color: Color::hsl(145.0, 0.7, 0.5),
-    //     perspective: true,
-    //     ..default()
-    // });
-    
-    let _res: Vec<Result<(), ()>> = io_pool.scope(|s| {
-        //let done = done.
-        let done = done.clone();
-        let buf = buf.clone();
-        let headers = headers.clone();
-        let sender = sender.clone();
-
-        // let task: Task = io_pool
-        //let task: bevy::tasks::Task<Result<(), Box<dyn std::error::Error + Send + Sync>>> = 
-        s
-            .spawn({
-                async move {
-                    while !done.load(std::sync::atomic::Ordering::Relaxed) {
-                        let Some(record) = buf.pop_ref() else {
-                            bevy::log::debug!("failed to pop record, yielding");
-                            std::thread::yield_now();
-                            continue;
-                        };
-
-                        if record.is_empty() {
-                            continue;
-                        }
+    if let Some(vparams) = loaded.vehicle_params {
+        bevy::log::info!("using vparams: {:?}", vparams);
+        commands.insert_resource(vparams);
+    }
 
-                        let tl = match record.deserialize(Some(&headers)) {
-                            Ok(tl) => tl,
-                            Err(e) => {
-                                bevy::log::error!("error deserializing record, skipping: {} (contents: {:#?})", e, record);
-                                continue;
-                            }
-                        };
+    if let Some(mtraj_res) = loaded.main_trajectory {
+        commands.spawn(make_main_trajectory_bundle(&mtraj_res));
+        commands.insert_resource(mtraj_res);
+    }
 
-                        if let Some(bundle) = make_trajectory_bundle(&tl) {
-                            sender.send((tl.time_step, bundle)).unwrap();
-                        }
+    if let Some((max_costs, trajectory_cache)) = loaded.trajectories {
+        commands.insert_resource(max_costs);
+        commands.insert_resource(trajectory_cache);
+    }
+}
 
-                        // if false {
-                        //     if let Some(bundle) =
-                        //         // make_trajectory_bundle(&tl)
-                        //         make_polyline_trajectory_bundle(&tl, &mut polyline_assets, &mut material_assets)
-                        //         {
-                        //         sender.send((tl.time_step, bundle)).unwrap();
-                        //     }
-                        // }
-                    }
-                    drop(sender);
-                    Ok(())
-                }
-            });
-        // task_list.push(task);
-        //}
-        // task.detach();
-    });
+fn spawn_trajectory_group(
+    commands: &mut Commands,
+    ts: i32,
+    trajectories: &[TrajectoryLog],
+    current: bool,
+    show_infeasible: bool,
+    max_costs: f64,
+    ego: Option<(&MainTrajectory, &VehicleParams)>,
+) -> Entity {
+    let mut group = commands.spawn((
+        Name::new(format!("trajectory group ts={}", ts)),
+        super::SceneEntity,
+        TrajectoryGroup { time_step: ts },
+        SpatialBundle {
+            visibility: if current { Visibility::Visible } else { Visibility::Hidden },
+            ..default()
+        },
+    ));
+    if current {
+        group.insert(CurrentTrajectoryGroup);
+    }
+    let ts_group = group.id();
 
-    drop(sender);
-*/
+    for tl in trajectories {
+        let Some((bundle, extra_bundle)) = make_trajectory_bundle(tl, max_costs) else {
+            continue;
+        };
 
-    let mut max_costs = f64::MIN_POSITIVE;
-    let mut ts_map = BTreeMap::new();
-    let bundles = traj
-        .iter()
-        .filter_map(|tl| {
-            let bundle = make_trajectory_bundle(&tl)?;
-            Some((tl.time_step, bundle))
-        });
-    // for (ts, (bundle, extra_bundle, costs)) in receiver.iter() {
-    for (ts, (bundle, extra_bundle, costs)) in bundles {
-        let ts_entity = ts_map.entry(ts).or_insert_with(|| {
-            commands
-                .spawn((
-                    Name::new(format!("trajectory group ts={}", ts)),
-                    TrajectoryGroup { time_step: ts },
-                    SpatialBundle::default(),
-                ))
-                .id()
-        });
         let mut entity = commands.spawn(bundle);
-        entity.set_parent(*ts_entity);
+        entity.set_parent(ts_group);
         if let Some(extra) = extra_bundle {
             entity.insert(extra);
         }
-        if costs > max_costs {
-            max_costs = costs;
+        if !tl.feasible && !show_infeasible {
+            entity.insert(Visibility::Hidden);
         }
     }
 
-    commands.insert_resource(MaxCosts { max_costs });
-
-    /*
-    for task in task_list.into_iter() {
-        bevy::tasks::block_on(async { task.await })
-            .expect("error running deserialization task");
+    if let Some((mtraj_res, vparams)) = ego {
+        spawn_ego_vehicle(commands, ts, ts_group, mtraj_res, vparams);
     }
-    */
-    
-    bevy::log::info!("using vparams: {:?}", vparams);
 
+    ts_group
+}
+
+fn spawn_ego_vehicle(
+    commands: &mut Commands,
+    ts: i32,
+    ts_group: Entity,
+    mtraj_res: &MainTrajectory,
+    vparams: &VehicleParams,
+) {
     let rect = crate::extra_shapes::RoundedRectangle {
         extents: Vec2::new(vparams.length, vparams.width),
         origin: RectangleOrigin::Center,
@@ -634,169 +577,167 @@ pub fn spawn_trajectories(
         origin: RectangleOrigin::Center,
     };
 
-    for (ts, ts_group) in ts_map.iter() {
-        let _span = bevy::log::debug_span!("processing trajectory time step", time_step=ts).entered();
+    let _span = bevy::log::debug_span!("processing trajectory time step", time_step=ts).entered();
 
-        let ts_idx = *ts as usize;
-        let Some(pos) = mtraj_res.kinematic_data.positions().nth(ts_idx) else {
-            bevy::log::error!("failed to add main trajectory data for ts={}", ts);
-            continue;
-        };
+    let ts_idx = ts as usize;
+    let kinematic_data = &mtraj_res.kinematic_data;
+    let (Some(pos), Some(&theta), Some(&curvature)) = (
+        kinematic_data.positions().nth(ts_idx),
+        kinematic_data.theta_orientations_rad.get(ts_idx),
+        kinematic_data.kappa_rad.get(ts_idx),
+    ) else {
+        bevy::log::error!("failed to add main trajectory data for ts={}", ts);
+        return;
+    };
 
-        let wheel_marker = crate::extra_shapes::RoundedRectangle {
-            extents: Vec2::new(0.8, 0.25),
-            origin: RectangleOrigin::Center,
-            radius: 0.1,
-        };
-        let wheel_fill = {
-            let mut fill = Fill::color(bevy::color::palettes::css::DARK_SLATE_GRAY);
-            fill.options.handle_intersections = false;
-            fill.options.tolerance = 1e-3;
-            fill
-        };
-        commands.spawn((
-                Name::new("ego obstacle"),
-                ShapeBundle {
-                    path: GeometryBuilder::build_as(&rect),
-                    spatial: SpatialBundle {
-                        transform: {
-                            let theta = *mtraj_res.kinematic_data.theta_orientations_rad.get(ts_idx).unwrap();
-                            let rotation = Quat::from_rotation_z(theta);
-
-                            let _orientation_transform0 = Transform::from_rotation(rotation);
-                            let mut orientation_transform = Transform::default();
-                            // orientation_transform.translation += rear_wheelbase;
-                            orientation_transform.rotate(rotation);
-                            // orientation_transform.translation -= rear_wheelbase;
-                            let pos_transform  = Transform::from_translation(pos.extend(20.0));
-                            let transform = pos_transform
-                                .mul_transform(orientation_transform);
-                            transform
-                        },
-                        ..default()
+    let wheel_marker = crate::extra_shapes::RoundedRectangle {
+        extents: Vec2::new(0.8, 0.25),
+        origin: RectangleOrigin::Center,
+        radius: 0.1,
+    };
+    let wheel_fill = {
+        let mut fill = Fill::color(bevy::color::palettes::css::DARK_SLATE_GRAY);
+        fill.options.handle_intersections = false;
+        fill.options.tolerance = 1e-3;
+        fill
+    };
+    commands.spawn((
+            Name::new("ego obstacle"),
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&rect),
+                spatial: SpatialBundle {
+                    transform: {
+                        let rotation = Quat::from_rotation_z(theta);
+
+                        let _orientation_transform0 = Transform::from_rotation(rotation);
+                        let mut orientation_transform = Transform::default();
+                        // orientation_transform.translation += rear_wheelbase;
+                        orientation_transform.rotate(rotation);
+                        // orientation_transform.translation -= rear_wheelbase;
+                        let pos_transform  = Transform::from_translation(pos.extend(20.0));
+                        let transform = pos_transform
+                            .mul_transform(orientation_transform);
+                        transform
                     },
                     ..default()
                 },
-                // super::HoverTooltip::bundle("Ego Vehicle"),
-                {
-                    let mut fill = Fill::color(Color::WHITE);
-                    fill.options.handle_intersections = false;
-                    fill.options.tolerance = 1e-2;
-                    fill
-                },
-                {
-                    let mut stroke = Stroke::new(bevy::color::palettes::css::ORANGE_RED, 0.1);
-                    stroke.options.tolerance = 1e-2;
-                    // stroke.options.line_join = LineJoin::Round;
-                    // stroke.options.start_cap = LineCap::Round;
-                    // stroke.options.end_cap = LineCap::Round;
-                    stroke
-                },
-            ))
-            .set_parent(*ts_group)
-            .with_children(|builder| {
-                builder
-                    .spawn((
-                        Name::new("rear wheelbase marker"),
+                ..default()
+            },
+            // super::HoverTooltip::bundle("Ego Vehicle"),
+            {
+                let mut fill = Fill::color(Color::WHITE);
+                fill.options.handle_intersections = false;
+                fill.options.tolerance = 1e-2;
+                fill
+            },
+            {
+                let mut stroke = Stroke::new(bevy::color::palettes::css::ORANGE_RED, 0.1);
+                stroke.options.tolerance = 1e-2;
+                // stroke.options.line_join = LineJoin::Round;
+                // stroke.options.start_cap = LineCap::Round;
+                // stroke.options.end_cap = LineCap::Round;
+                stroke
+            },
+        ))
+        .set_parent(ts_group)
+        .with_children(|builder| {
+            builder
+                .spawn((
+                    Name::new("rear wheelbase marker"),
+                    ShapeBundle {
+                        path: GeometryBuilder::build_as(&wheelbase_marker),
+                        spatial: SpatialBundle {
+                            transform: {
+                                Transform::from_translation(vparams.rear().extend(1.0))
+                            },
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Fill::color(bevy::color::palettes::css::GRAY),
+                    super::HoverTooltip::bundle("Rear Wheelbase"),
+                )).with_children(|builder| {
+                    builder.spawn((
+                        Name::new("left rear wheel"),
                         ShapeBundle {
-                            path: GeometryBuilder::build_as(&wheelbase_marker),
+                            path: GeometryBuilder::build_as(&wheel_marker),
                             spatial: SpatialBundle {
                                 transform: {
-                                    Transform::from_translation(vparams.rear().extend(1.0))
+                                    Transform::from_translation(vparams.left().extend(0.5))
                                 },
                                 ..default()
                             },
                             ..default()
                         },
-                        Fill::color(bevy::color::palettes::css::GRAY),
-                        super::HoverTooltip::bundle("Rear Wheelbase"),
-                    )).with_children(|builder| {
-                        builder.spawn((
-                            Name::new("left rear wheel"),
-                            ShapeBundle {
-                                path: GeometryBuilder::build_as(&wheel_marker),
-                                spatial: SpatialBundle {
-                                    transform: {
-                                        Transform::from_translation(vparams.left().extend(0.5))
-                                    },
-                                    ..default()
+                        wheel_fill,
+                    ));
+                    builder.spawn((
+                        Name::new("right rear wheel"),
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&wheel_marker),
+                            spatial: SpatialBundle {
+                                transform: {
+                                    Transform::from_translation(vparams.right().extend(0.5))
                                 },
                                 ..default()
                             },
-                            wheel_fill,
-                        ));
-                        builder.spawn((
-                            Name::new("right rear wheel"),
-                            ShapeBundle {
-                                path: GeometryBuilder::build_as(&wheel_marker),
-                                spatial: SpatialBundle {
-                                    transform: {
-                                        Transform::from_translation(vparams.right().extend(0.5))
-                                    },
-                                    ..default()
-                                },
-                                ..default()
+                            ..default()
+                        },
+                        wheel_fill,
+                    ));
+                });
+            builder
+                .spawn((
+                    Name::new("front wheelbase marker"),
+                    ShapeBundle {
+                        path: GeometryBuilder::build_as(&wheelbase_marker),
+                        spatial: SpatialBundle {
+                            transform: {
+                                Transform::from_translation(vparams.front().extend(1.0))
                             },
-                            wheel_fill,
-                        ));
-                    });
-                builder
-                    .spawn((
-                        Name::new("front wheelbase marker"),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Fill::color(bevy::color::palettes::css::GRAY),
+                    super::HoverTooltip::bundle("Front Wheelbase"),
+                )).with_children(|builder| {
+                    let steering_angle = (vparams.wheelbase * curvature).atan();
+
+                    builder.spawn((
+                        Name::new("left front wheel"),
                         ShapeBundle {
-                            path: GeometryBuilder::build_as(&wheelbase_marker),
+                            path: GeometryBuilder::build_as(&wheel_marker),
                             spatial: SpatialBundle {
                                 transform: {
-                                    Transform::from_translation(vparams.front().extend(1.0))
+                                    let mut transform = Transform::from_translation(vparams.left().extend(0.5));
+                                    transform.rotate_z(steering_angle);
+                                    transform
                                 },
                                 ..default()
                             },
                             ..default()
                         },
-                        Fill::color(bevy::color::palettes::css::GRAY),
-                        super::HoverTooltip::bundle("Front Wheelbase"),
-                    )).with_children(|builder| {
-                        let curvature = *mtraj_res.kinematic_data.kappa_rad.get(ts_idx).unwrap();
-
-                        let steering_angle = (vparams.wheelbase * curvature).atan();
-
-                        builder.spawn((
-                            Name::new("left front wheel"),
-                            ShapeBundle {
-                                path: GeometryBuilder::build_as(&wheel_marker),
-                                spatial: SpatialBundle {
-                                    transform: {
-                                        let mut transform = Transform::from_translation(vparams.left().extend(0.5));
-                                        transform.rotate_z(steering_angle);
-                                        transform
-                                    },
-                                    ..default()
-                                },
-                                ..default()
-                            },
-                            wheel_fill,
-                        ));
-                        builder.spawn((
-                            Name::new("right front wheel"),
-                            ShapeBundle {
-                                path: GeometryBuilder::build_as(&wheel_marker),
-                                spatial: SpatialBundle {
-                                    transform: {
-                                        let mut transform = Transform::from_translation(vparams.right().extend(0.5));
-                                        transform.rotate_z(steering_angle);
-                                        transform
-                                    },
-                                    ..default()
+                        wheel_fill,
+                    ));
+                    builder.spawn((
+                        Name::new("right front wheel"),
+                        ShapeBundle {
+                            path: GeometryBuilder::build_as(&wheel_marker),
+                            spatial: SpatialBundle {
+                                transform: {
+                                    let mut transform = Transform::from_translation(vparams.right().extend(0.5));
+                                    transform.rotate_z(steering_angle);
+                                    transform
                                 },
                                 ..default()
                             },
-                            wheel_fill,
-                        ));
-                    });
-            });
-    }
-
-    commands.insert_resource(mtraj_res);
+                            ..default()
+                        },
+                        wheel_fill,
+                    ));
+                });
+        });
 }
 
 pub(crate) fn trajectory_group_visibility(
@@ -889,19 +830,9 @@ pub(crate) fn trajectory_tooltip(
 
                 ui.label(format!("total cost: {}", traj.costs_cumulative_weighted));
                 // ui.label(format!("collision cost: {}", traj.costs.prediction_cost));
-                ui.label(format!("inf_kin_yaw_rate: {}", traj.inf_kin_yaw_rate));
-                ui.label(format!(
-                    "inf_kin_acceleration: {}",
-                    traj.inf_kin_acceleration
-                ));
-                ui.label(format!(
-                    "inf_kin_max_curvature: {}",
-                    traj.inf_kin_max_curvature
-                ));
-                ui.label(format!(
-                    "inf_kin_max_curvature_rate: {}",
-                    traj.inf_kin_max_curvature_rate
-                ));
+                for (name, value) in traj.sorted_infeasibility() {
+                    ui.label(format!("{}: {}", name, value));
+                }
                 // plot_traj(traj, ui, cts.dynamic_time_step.round());
             }
         },
@@ -915,6 +846,7 @@ fn trajectory_description(
     time_step: f32,
     issues_detected: bool,
     vparams: &VehicleParams,
+    speed_limits: &[super::traffic_sign::SpeedLimit],
 ) -> (bool, Option<f64>) {
     let mut xcursor = None;
 
@@ -1185,34 +1117,20 @@ fn trajectory_description(
                             });
                         })
                         .body(|mut body| {
-                            macro_rules! inf_row {
-                                ($inf_name:ident) => {
-                                    body.row(18.0, |mut row| {
-                                        row.col(|ui| {
-                                            ui.monospace(std::stringify!($inf_name));
-                                        });
-                                        row.col(|ui| {
-                                            ui.with_layout(value_cell_layout, |ui| {
-                                                let inf_val: i32 = traj.$inf_name;
-                                                let text = egui::RichText::new(format!(
-                                                    "{:>5.0}",
-                                                    inf_val
-                                                )).monospace();
-                                                let resp = ui.label(if inf_val == 0 {
-                                                    text.weak()
-                                                } else {
-                                                    text
-                                                });
-                                                resp.on_hover_text(inf_val.to_string());
-                                            });
+                            for (name, value) in traj.sorted_infeasibility() {
+                                body.row(18.0, |mut row| {
+                                    row.col(|ui| {
+                                        ui.monospace(name);
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(value_cell_layout, |ui| {
+                                            let text = egui::RichText::new(format!("{:>5}", value)).monospace();
+                                            let resp = ui.label(if value == 0.0 { text.weak() } else { text });
+                                            resp.on_hover_text(value.to_string());
                                         });
                                     });
-                                };
+                                });
                             }
-                            inf_row!(inf_kin_yaw_rate);
-                            inf_row!(inf_kin_acceleration);
-                            inf_row!(inf_kin_max_curvature);
-                            inf_row!(inf_kin_max_curvature_rate);
                         });
                 });
 
@@ -1220,7 +1138,7 @@ fn trajectory_description(
 
                 ui.separator();
 
-                xcursor = plot::plot_traj(plot_data, ui, time_step, vparams)
+                xcursor = plot::plot_traj(plot_data, ui, time_step, vparams, speed_limits)
             });
         });
 
@@ -1249,6 +1167,8 @@ pub(crate) fn trajectory_window(
     mut cached_plot_data: Local<Option<std::sync::Arc<plot::CachedTrajectoryPlotData>>>,
 
     vparams: Res<VehicleParams>,
+
+    speed_limits: Option<Res<super::traffic_sign::SpeedLimits>>,
 ) {
     let ctx = contexts.ctx_mut();
 
@@ -1290,6 +1210,7 @@ pub(crate) fn trajectory_window(
                         cts.dynamic_time_step.round(),
                         invalid_data,
                         &vparams,
+                        speed_limits.as_ref().map_or(&[][..], |limits| limits.0.as_slice()),
                     );
                     if new_issues_detected && invalid_data != new_issues_detected {
                         commands.entity(entity).insert(HasInvalidData);
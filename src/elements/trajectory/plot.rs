@@ -150,6 +150,7 @@ pub(crate) fn plot_traj(
     ui: &mut egui::Ui,
     time_step: f32,
     vparams: &super::VehicleParams,
+    speed_limits: &[crate::elements::traffic_sign::SpeedLimit],
 ) -> Option<f64> {
     let mut cursor_x = None;
 
@@ -249,6 +250,16 @@ pub(crate) fn plot_traj(
             //     egui_plot::HLine::new(vparams.v_max).style(egui_plot::LineStyle::Dashed { length: 10.0 }),
             // ); // .name("v_max"));
 
+            // The lanelets are named, as the limits do not necessarily apply along the whole trajectory
+            for speed_limit in speed_limits {
+                pui.hline(
+                    egui_plot::HLine::new(speed_limit.limit)
+                        .name(speed_limit.label())
+                        .color(egui::Color32::LIGHT_RED)
+                        .style(egui_plot::LineStyle::Dashed { length: 10.0 }),
+                );
+            }
+
             if let Some(pointer) = pui.pointer_coordinate() {
                 cursor_x = Some(pointer.x);
             }
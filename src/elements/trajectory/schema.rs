@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use crate::loading::LoadError;
+
+use super::{KinematicData, TrajectoryLog};
+
+/// Columns used to join the trajectory tables
+const KEY_COLUMNS: [&str; 2] = ["time_step", "id"];
+
+/// Columns every planner version writes, either to `trajectories` or `trajectories_meta`
+const REQUIRED_COLUMNS: &[&str] = &[
+    "x",
+    "y",
+    "theta",
+    "kappa",
+    "curvilinear_theta",
+    "v",
+    "a",
+    "feasible",
+    "horizon",
+    "dt",
+    "s_position",
+    "d_position",
+    "costs_cumulative_weighted",
+];
+
+/// Layout of the `trajectories.db` as detected from the tables it contains.
+///
+/// Planner versions differ in which tables and columns they write,
+/// so the query and the columns read from each row are selected from this.
+#[derive(Debug, Clone)]
+pub(crate) struct TrajectorySchema {
+    joined_tables: Vec<&'static str>,
+    /// Table containing each trajectory column
+    columns: HashMap<String, &'static str>,
+    cost_columns: Vec<String>,
+    infeasibility_columns: Vec<String>,
+}
+
+fn table_columns(conn: &rusqlite::Connection, table: &str) -> Result<Vec<String>, LoadError> {
+    // PRAGMA statements do not support parameters, but the table names are fixed
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+
+    let columns = stmt
+        .query_map([], |row| row.get("name"))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    Ok(columns)
+}
+
+fn value_columns(columns: Vec<String>) -> Vec<String> {
+    columns
+        .into_iter()
+        .filter(|name| !KEY_COLUMNS.contains(&name.as_str()))
+        .collect()
+}
+
+impl TrajectorySchema {
+    pub(crate) fn probe(conn: &rusqlite::Connection) -> Result<Self, LoadError> {
+        let trajectory_columns = table_columns(conn, "trajectories")?;
+        if trajectory_columns.is_empty() {
+            return Err(LoadError::schema("missing table 'trajectories'"));
+        }
+
+        let mut schema = Self {
+            joined_tables: vec![],
+            columns: trajectory_columns
+                .into_iter()
+                .map(|name| (name, "trajectories"))
+                .collect(),
+            cost_columns: vec![],
+            infeasibility_columns: vec![],
+        };
+
+        for key in KEY_COLUMNS {
+            if !schema.has_column(key) {
+                return Err(LoadError::schema(format!("missing column '{}' in table 'trajectories'", key)));
+            }
+        }
+
+        let meta_columns = table_columns(conn, "trajectories_meta")?;
+        if !meta_columns.is_empty() {
+            schema.joined_tables.push("trajectories_meta");
+            schema.columns.extend(
+                value_columns(meta_columns)
+                    .into_iter()
+                    .map(|name| (name, "trajectories_meta")),
+            );
+        }
+
+        let cost_columns = table_columns(conn, "costs")?;
+        if !cost_columns.is_empty() {
+            schema.joined_tables.push("costs");
+            schema.cost_columns = value_columns(cost_columns);
+        }
+
+        // Older planner versions misspell the table name
+        for table in ["infeasability", "infeasibility"] {
+            let infeasibility_columns = table_columns(conn, table)?;
+            if !infeasibility_columns.is_empty() {
+                schema.joined_tables.push(table);
+                schema.infeasibility_columns = value_columns(infeasibility_columns);
+                break;
+            }
+        }
+
+        let missing: Vec<_> = REQUIRED_COLUMNS
+            .iter()
+            .filter(|name| !schema.has_column(name))
+            .copied()
+            .collect();
+        if !missing.is_empty() {
+            return Err(LoadError::schema(format!(
+                "missing trajectory columns: {}",
+                missing.join(", ")
+            )));
+        }
+
+        bevy::log::info!(
+            "trajectories.db schema: tables={:?} costs={} infeasibility columns={}",
+            schema.joined_tables,
+            schema.cost_columns.len(),
+            schema.infeasibility_columns.len(),
+        );
+
+        Ok(schema)
+    }
+
+    fn has_column(&self, name: &str) -> bool {
+        self.columns.contains_key(name)
+    }
+
+    /// Table containing the total cost of each trajectory.
+    pub(crate) fn costs_table(&self) -> &'static str {
+        self.columns["costs_cumulative_weighted"]
+    }
+
+    /// Query selecting the trajectories of a single time step (parameter `?1`)
+    /// together with the data from all joined tables.
+    pub(crate) fn select_query(&self) -> String {
+        let mut query = "SELECT * FROM trajectories".to_string();
+
+        for table in self.joined_tables.iter() {
+            query.push_str(&format!(" INNER JOIN {} USING (time_step, id)", table));
+        }
+
+        query.push_str(" WHERE time_step = ?1");
+
+        query
+    }
+
+    fn optional_column<T: rusqlite::types::FromSql>(
+        &self,
+        row: &rusqlite::Row,
+        name: &str,
+    ) -> rusqlite::Result<Option<T>> {
+        if self.has_column(name) {
+            row.get(name)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn optional_json_column(&self, row: &rusqlite::Row, name: &str) -> rusqlite::Result<Vec<f32>> {
+        if self.has_column(name) {
+            crate::loading::json_column(row, name)
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    pub(crate) fn read_trajectory(&self, row: &rusqlite::Row) -> rusqlite::Result<TrajectoryLog> {
+        let convert = |name| -> rusqlite::Result<Vec<f32>> {
+            crate::loading::json_column(row, name)
+        };
+        let kd = KinematicData {
+            x_positions_m: convert("x")?,
+            y_positions_m: convert("y")?,
+            theta_orientations_rad: convert("theta")?,
+            kappa_rad: convert("kappa")?,
+            curvilinear_orientations_rad: convert("curvilinear_theta")?,
+            velocities_mps: convert("v")?,
+            accelerations_mps2: convert("a")?,
+            trajectory_long: self.optional_json_column(row, "trajectory_long")?,
+            trajectory_lat: self.optional_json_column(row, "trajectory_lat")?,
+        };
+
+        let fetch_map = |names: &Vec<String>| -> rusqlite::Result<HashMap<String, f64>> {
+            names
+                .iter()
+                .map(|name| Ok((name.clone(), row.get(name.as_str())?)))
+                .collect()
+        };
+
+        Ok(TrajectoryLog {
+            time_step: row.get("time_step")?,
+            // TODO: remove?
+            trajectory_number: -1,
+            unique_id: row.get("id")?,
+            feasible: row.get("feasible")?,
+            horizon: row.get("horizon")?,
+            dt: row.get("dt")?,
+            kinematic_data: kd,
+            s_position_m: row.get("s_position")?,
+            d_position_m: row.get("d_position")?,
+            ego_risk: self.optional_column(row, "ego_risk")?,
+            obst_risk: self.optional_column(row, "obst_risk")?,
+            collision_detected: self.optional_column(row, "collision_detected")?,
+            boundary_harm: self.optional_column(row, "boundary_harm")?,
+            costs_cumulative_weighted: row.get("costs_cumulative_weighted")?,
+            costs: fetch_map(&self.cost_columns)?,
+            infeasibility: fetch_map(&self.infeasibility_columns)?,
+        })
+    }
+}
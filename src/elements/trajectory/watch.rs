@@ -0,0 +1,278 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use crate::loading::{LoadDiagnostics, LoadError, LoadingProgress};
+
+use super::{
+    LogsLoadingTask, MainTrajectory, MainTrajectoryPath, SelectTrajectoryEvent, SelectedTrajectory, TrajectoryGroup,
+    TrajectoryLog,
+};
+
+/// Log of the main trajectory, changes to it alone only reload the main trajectory
+const MAIN_LOG: &str = "logs.csv";
+
+/// Files in the logs directory that are reloaded when the planner rewrites them
+///
+/// Not `trajectories.db-wal`, our own connections to the database touch it as well,
+/// which would reload the logs over and over again.
+const WATCHED_FILES: [&str; 2] = [MAIN_LOG, "trajectories.db"];
+
+/// The planner writes the logs over some time, so wait until the files stopped changing
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// Watches the logs directory for changes made by a new planner run.
+#[derive(Resource)]
+pub(crate) struct LogsWatcher {
+    logs: std::path::PathBuf,
+    last_change: Arc<Mutex<Option<LogsChange>>>,
+    // Stops watching once dropped
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// Changes to the logs that were not reloaded yet.
+#[derive(Clone, Copy, Debug)]
+struct LogsChange {
+    time: Instant,
+    /// Whether anything besides the main log changed
+    trajectories: bool,
+}
+
+/// Reloads only the main trajectory, after only `logs.csv` changed.
+#[derive(Resource)]
+pub(crate) struct MainLogLoadingTask(Task<Result<MainTrajectory, LoadError>>);
+
+/// Trajectory to select again once the current trajectory group was reloaded.
+#[derive(Resource)]
+pub(crate) struct RestoreSelection {
+    unique_id: i32,
+}
+
+impl LogsWatcher {
+    /// `event_loop` is woken up on changes, otherwise they are only noticed on the next input.
+    pub(crate) fn new(
+        logs: &std::path::Path,
+        event_loop: Option<bevy::winit::EventLoopProxy<bevy::winit::WakeUp>>,
+    ) -> notify::Result<Self> {
+        use notify::Watcher;
+
+        let last_change = Arc::new(Mutex::new(None));
+
+        let mut watcher = notify::recommended_watcher({
+            let last_change = last_change.clone();
+
+            move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(err) => {
+                        bevy::log::warn!("error watching logs directory: {}", err);
+                        return;
+                    }
+                };
+
+                let changed = changed_log_files(&event);
+                if changed.is_empty() {
+                    return;
+                }
+
+                bevy::log::debug!("logs changed: {:?}", event);
+                let mut last_change = last_change.lock().unwrap_or_else(PoisonError::into_inner);
+                let trajectories = changed.iter().any(|name| *name != MAIN_LOG)
+                    || last_change.is_some_and(|change| change.trajectories);
+                *last_change = Some(LogsChange {
+                    time: Instant::now(),
+                    trajectories,
+                });
+
+                if let Some(event_loop) = &event_loop {
+                    // Fails only if the app is already closing
+                    let _ = event_loop.send_event(bevy::winit::WakeUp);
+                }
+            }
+        })?;
+
+        // Watch the directory instead of the files, the planner replaces them on every run
+        watcher.watch(logs, notify::RecursiveMode::NonRecursive)?;
+
+        bevy::log::info!("watching {} for changes", logs.display());
+
+        Ok(Self {
+            logs: logs.to_owned(),
+            last_change,
+            _watcher: watcher,
+        })
+    }
+
+    /// Last change that was not reloaded yet.
+    fn last_change(&self) -> Option<LogsChange> {
+        *self.last_change.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn clear(&self) {
+        *self.last_change.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+/// Names of the watched files changed by the event.
+fn changed_log_files(event: &notify::Event) -> Vec<&str> {
+    use notify::event::{EventKind, ModifyKind};
+
+    let mutating = match event.kind {
+        EventKind::Create(_) | EventKind::Remove(_) => true,
+        EventKind::Modify(ModifyKind::Metadata(_)) => false,
+        EventKind::Modify(_) => true,
+        EventKind::Any | EventKind::Access(_) | EventKind::Other => false,
+    };
+
+    if !mutating {
+        return Vec::new();
+    }
+
+    event
+        .paths
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str())
+        .filter(|name| WATCHED_FILES.contains(name))
+        .collect()
+}
+
+/// Reloads the planner logs in place once the watched files stopped changing.
+///
+/// The scenario, the camera and the current time step are kept,
+/// the selected trajectory is selected again if it still exists.
+/// If only the main log changed, the trajectories are not read again.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn reload_changed_logs(
+    mut commands: Commands,
+    watcher: Res<LogsWatcher>,
+    loading_task: Option<Res<LogsLoadingTask>>,
+    main_log_task: Option<Res<MainLogLoadingTask>>,
+    progress: Res<LoadingProgress>,
+
+    logs_q: Query<Entity, Or<(With<TrajectoryGroup>, With<MainTrajectoryPath>)>>,
+    selected_q: Query<&TrajectoryLog, With<SelectedTrajectory>>,
+
+    mut redraw_request_events: EventWriter<bevy::window::RequestRedraw>,
+) {
+    let Some(last_change) = watcher.last_change() else {
+        return;
+    };
+
+    // Keep updating until the changes settled
+    redraw_request_events.send(bevy::window::RequestRedraw);
+
+    // A run that is still being loaded is reloaded once it finished
+    if last_change.time.elapsed() < SETTLE_TIME || loading_task.is_some() || main_log_task.is_some() {
+        return;
+    }
+
+    watcher.clear();
+
+    if !last_change.trajectories {
+        bevy::log::info!("reloading the main trajectory from {}", watcher.logs.display());
+
+        let main_log_path = watcher.logs.join(MAIN_LOG);
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            // Progress is only shown for loading all logs
+            let progress = LoadingProgress::default();
+            let main_trajectories = super::log::read_main_log(&main_log_path, &progress)?;
+
+            super::make_main_trajectory(&main_trajectories)
+        });
+        commands.insert_resource(MainLogLoadingTask(task));
+        return;
+    }
+
+    bevy::log::info!("reloading planner logs from {}", watcher.logs.display());
+
+    if let Ok(selected) = selected_q.get_single() {
+        commands.insert_resource(RestoreSelection {
+            unique_id: selected.unique_id,
+        });
+    }
+
+    for entity in logs_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    super::remove_logs(&mut commands);
+
+    commands.insert_resource(LogsLoadingTask::spawn(watcher.logs.clone(), progress.clone()));
+}
+
+/// Replaces the main trajectory once it was reloaded.
+///
+/// The trajectory groups show the ego vehicle along the main trajectory, so they are respawned as well.
+pub(crate) fn finish_reloading_main_log(
+    mut commands: Commands,
+    mut task: ResMut<MainLogLoadingTask>,
+    cache: Option<ResMut<super::cache::TrajectoryCache>>,
+    mut diagnostics: ResMut<LoadDiagnostics>,
+
+    main_path_q: Query<Entity, With<MainTrajectoryPath>>,
+    selected_q: Query<&TrajectoryLog, With<SelectedTrajectory>>,
+) {
+    let Some(result) = bevy::tasks::block_on(bevy::tasks::poll_once(&mut task.0)) else {
+        return;
+    };
+
+    commands.remove_resource::<MainLogLoadingTask>();
+
+    let Some(mtraj) = diagnostics.ok("main trajectory", result) else {
+        return;
+    };
+
+    for entity in main_path_q.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.spawn(super::make_main_trajectory_bundle(&mtraj));
+    commands.insert_resource(mtraj);
+
+    if let Some(mut cache) = cache {
+        if let Ok(selected) = selected_q.get_single() {
+            commands.insert_resource(RestoreSelection {
+                unique_id: selected.unique_id,
+            });
+        }
+
+        cache.despawn_groups(&mut commands);
+    }
+}
+
+/// Selects the previously selected trajectory again once the current trajectory group was spawned.
+pub(crate) fn restore_selection(
+    mut commands: Commands,
+    restore: Res<RestoreSelection>,
+    cache: Option<Res<super::cache::TrajectoryCache>>,
+    time_step: Res<crate::global_settings::TimeStep>,
+
+    trajectory_q: Query<(Entity, &TrajectoryLog)>,
+
+    mut selection_events: EventWriter<SelectTrajectoryEvent>,
+) {
+    let Some(cache) = cache else {
+        return;
+    };
+
+    if !cache.is_loaded(time_step.time_step) {
+        return;
+    }
+
+    let selected = trajectory_q
+        .iter()
+        .find(|(_entity, traj)| traj.time_step == time_step.time_step && traj.unique_id == restore.unique_id);
+
+    match selected {
+        Some((entity, _traj)) => {
+            selection_events.send(SelectTrajectoryEvent(entity));
+        }
+        None => {
+            bevy::log::info!("trajectory {} no longer exists after reloading", restore.unique_id);
+        }
+    }
+
+    commands.remove_resource::<RestoreSelection>();
+}
@@ -107,4 +107,28 @@ impl<'a, T> Geometry for PolylineRef<'a, T> {
         }
     }
 }
- */
\ No newline at end of file
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrow {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub head_length: f32,
+}
+
+impl Geometry for Arrow {
+    fn add_geometry(&self, b: &mut lyon_path::path::Builder) {
+        let direction = (self.to - self.from).normalize_or_zero() * self.head_length;
+        let normal = direction.perp() * 0.6;
+
+        let to_point = |v: Vec2| Point::new(v.x, v.y);
+
+        b.begin(to_point(self.from));
+        b.line_to(to_point(self.to));
+        b.end(false);
+
+        b.begin(to_point(self.to - direction + normal));
+        b.line_to(to_point(self.to));
+        b.line_to(to_point(self.to - direction - normal));
+        b.end(false);
+    }
+}
@@ -2,6 +2,8 @@ use bevy::{prelude::*, window::RequestRedraw};
 
 use bevy_egui::EguiContexts;
 
+use crate::commonroad_pb::road_user_enum::RoadUser;
+
 pub struct GlobalSettingsPlugin;
 
 impl Plugin for GlobalSettingsPlugin {
@@ -20,20 +22,46 @@ impl Plugin for GlobalSettingsPlugin {
 #[derive(Resource, Clone, PartialEq)]
 pub struct GlobalSettings {
     pub show_infeasible: bool,
+    /// Overlay the occupancies of all future time steps instead of only the current one
+    pub show_future_occupancies: bool,
+    /// Draw the centerline of each lanelet with chevrons in the driving direction
+    pub show_centerlines: bool,
+    pub show_intersections: bool,
+    pub lanelet_coloring: LaneletColoring,
+    /// Vehicle class of the ego vehicle, for [`LaneletColoring::EgoAllowed`]
+    pub ego_road_user: RoadUser,
     pub enable_time_animation: bool,
     pub time_animation_speed: f32,
+    /// Jump to the newest time step when following a running planner
+    pub follow_newest_time_step: bool,
 }
 
 impl Default for GlobalSettings {
     fn default() -> Self {
         Self {
             show_infeasible: false,
+            show_future_occupancies: false,
+            show_centerlines: false,
+            show_intersections: false,
+            lanelet_coloring: LaneletColoring::Plain,
+            ego_road_user: RoadUser::Car,
             enable_time_animation: false,
             time_animation_speed: 5.0,
+            follow_newest_time_step: true,
         }
     }
 }
 
+/// How the lanelet backgrounds are coloured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LaneletColoring {
+    Plain,
+    /// By the most specific of the lanelet types
+    Type,
+    /// By whether the ego vehicle class may use the lanelet
+    EgoAllowed,
+}
+
 #[derive(Resource)]
 pub struct CurrentTimeStep {
     pub dynamic_time_step: f32,
@@ -88,6 +116,7 @@ pub fn side_panel(
     mut ts: ResMut<TimeStep>,
     mut settings: ResMut<GlobalSettings>,
     cr: Res<crate::CommonRoad>,
+    args: Res<crate::args::Args>,
 ) {
     let ctx = contexts.ctx_mut();
 
@@ -99,6 +128,52 @@ pub fn side_panel(
                 &mut new_settings.show_infeasible,
                 "Show infeasible trajectories",
             );
+            ui.checkbox(
+                &mut new_settings.show_future_occupancies,
+                "Show future occupancies",
+            ).on_hover_text("Overlay the occupancies of set-based predictions for all future time steps");
+            ui.checkbox(
+                &mut new_settings.show_centerlines,
+                "Show lanelet centerlines",
+            ).on_hover_text("Centerlines with chevrons in the driving direction of each lanelet");
+            ui.checkbox(
+                &mut new_settings.show_intersections,
+                "Show intersections",
+            ).on_hover_text("Click an incoming to highlight its successors and the crossing lanelets");
+
+            ui.separator();
+
+            egui::ComboBox::from_label("Lanelet colours")
+                .selected_text(match new_settings.lanelet_coloring {
+                    LaneletColoring::Plain => "plain",
+                    LaneletColoring::Type => "by type",
+                    LaneletColoring::EgoAllowed => "by ego access",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut new_settings.lanelet_coloring, LaneletColoring::Plain, "plain");
+                    ui.selectable_value(&mut new_settings.lanelet_coloring, LaneletColoring::Type, "by type");
+                    ui.selectable_value(&mut new_settings.lanelet_coloring, LaneletColoring::EgoAllowed, "by ego access");
+                });
+
+            if new_settings.lanelet_coloring == LaneletColoring::EgoAllowed {
+                egui::ComboBox::from_label("Ego vehicle class")
+                    .selected_text(format!("{:?}", new_settings.ego_road_user))
+                    .show_ui(ui, |ui| {
+                        for user in [
+                            RoadUser::Car,
+                            RoadUser::Truck,
+                            RoadUser::Bus,
+                            RoadUser::PriorityVehicle,
+                            RoadUser::Motorcycle,
+                            RoadUser::Taxi,
+                            RoadUser::Bicycle,
+                        ] {
+                            ui.selectable_value(&mut new_settings.ego_road_user, user, format!("{:?}", user));
+                        }
+                    });
+            }
+
+            crate::elements::lanelet::lanelet_color_legend(ui, &new_settings);
         });
 
     // let panel_id = egui::Id::new("side panel left");
@@ -109,9 +184,6 @@ pub fn side_panel(
         .default_width(310.0)
         .resizable(false)
         .show(ctx, |ui| {
-            // ui.heading("Scenario Information");
-            // ui.label(format!("{:#?}", cr.information));
-
             //ctx.settings_ui(ui);
 
             ui.set_max_width(310.0);
@@ -181,6 +253,11 @@ pub fn side_panel(
                     .clamp_to_range(true),
             ).on_hover_text("Speed in actual time per seconds (1\u{00D7} is realtime)");
             new_settings.time_animation_speed = realtime_speed / cr.information.time_step_size as f32;
+
+            if args.live {
+                ui.checkbox(&mut new_settings.follow_newest_time_step, "Follow newest time step")
+                    .on_hover_text("Show new time steps as soon as the planner wrote them");
+            }
         });
 
     settings.set_if_neq(new_settings);
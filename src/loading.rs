@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .init_resource::<LoadDiagnostics>()
+            .init_resource::<LoadingProgress>()
+            .add_systems(Update, (load_diagnostics_window, loading_screen));
+    }
+}
+
+/// The scenario is shown right away, while the planner logs are loaded in the background.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AppState {
+    /// Entered on startup and whenever another run is opened, despawns the previous run.
+    #[default]
+    Unloaded,
+    Loading,
+    Running,
+}
+
+/// Progress of the current loading stage, shared with the background loading task.
+#[derive(Resource, Clone, Default)]
+pub struct LoadingProgress(std::sync::Arc<ProgressCounter>);
+
+#[derive(Default)]
+struct ProgressCounter {
+    /// Current stage, `None` if nothing is being loaded
+    stage: std::sync::Mutex<Option<&'static str>>,
+    done: std::sync::atomic::AtomicU64,
+    total: std::sync::atomic::AtomicU64,
+}
+
+impl LoadingProgress {
+    /// Starts a new stage, `total` is the number of rows (or bytes) to process or zero if unknown.
+    pub(crate) fn start_stage(&self, stage: &'static str, total: u64) {
+        use std::sync::atomic::Ordering;
+
+        bevy::log::debug!("loading: {}", stage);
+
+        *self.0.stage.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = Some(stage);
+        self.0.done.store(0, Ordering::Relaxed);
+        self.0.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Hides the loading screen until the next stage is started.
+    pub(crate) fn finish(&self) {
+        *self.0.stage.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    pub(crate) fn set_done(&self, done: u64) {
+        self.0.done.store(done, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn stage(&self) -> Option<&'static str> {
+        *self.0.stage.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn fraction(&self) -> Option<f32> {
+        use std::sync::atomic::Ordering;
+
+        let total = self.0.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return None;
+        }
+
+        let done = self.0.done.load(Ordering::Relaxed);
+
+        Some((done as f64 / total as f64).clamp(0.0, 1.0) as f32)
+    }
+}
+
+pub fn loading_screen(
+    mut contexts: EguiContexts,
+    progress: Res<LoadingProgress>,
+    mut redraw_request_events: EventWriter<bevy::window::RequestRedraw>,
+) {
+    let Some(stage) = progress.stage() else {
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new("Loading planner logs")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0.0, -40.0))
+        .show(ctx, |ui| {
+            ui.label(stage);
+
+            let progress_bar = match progress.fraction() {
+                Some(fraction) => egui::ProgressBar::new(fraction).show_percentage(),
+                None => egui::ProgressBar::new(0.0).animate(true),
+            };
+            ui.add(progress_bar.desired_width(300.0));
+        });
+
+    // Keep updating the progress bar even without any input
+    redraw_request_events.send(bevy::window::RequestRedraw);
+}
+
+/// Errors that can occur while reading scenarios and planner logs.
+#[derive(Debug)]
+pub enum LoadError {
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    Sqlite(rusqlite::Error),
+    Protobuf {
+        path: std::path::PathBuf,
+        source: prost::DecodeError,
+    },
+    Xml {
+        path: std::path::PathBuf,
+        source: crate::commonroad_xml::XmlError,
+    },
+    Json {
+        what: String,
+    },
+    Csv {
+        path: std::path::PathBuf,
+        source: csv::Error,
+    },
+    /// The data does not have the expected layout,
+    /// e.g. a missing column or `meta` key, or an unexpected NULL value.
+    Schema(String),
+}
+
+impl LoadError {
+    pub(crate) fn io(path: &std::path::Path) -> impl FnOnce(std::io::Error) -> Self + '_ {
+        move |source| LoadError::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub(crate) fn protobuf(path: &std::path::Path) -> impl FnOnce(prost::DecodeError) -> Self + '_ {
+        move |source| LoadError::Protobuf {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub(crate) fn xml(path: &std::path::Path) -> impl FnOnce(crate::commonroad_xml::XmlError) -> Self + '_ {
+        move |source| LoadError::Xml {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    pub(crate) fn schema(message: impl Into<String>) -> Self {
+        LoadError::Schema(message.into())
+    }
+
+    pub(crate) fn json(what: impl Into<String>) -> Self {
+        LoadError::Json { what: what.into() }
+    }
+
+    /// Wraps the error so that it can be returned from rusqlite row mapping closures,
+    /// it is unwrapped again by the `From<rusqlite::Error>` conversion.
+    pub(crate) fn into_sqlite(self) -> rusqlite::Error {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Null, Box::new(self))
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io { source, .. } => Some(source),
+            LoadError::Sqlite(err) => Some(err),
+            LoadError::Protobuf { source, .. } => Some(source),
+            LoadError::Xml { source, .. } => Some(source),
+            LoadError::Csv { source, .. } => Some(source),
+            LoadError::Json { .. } | LoadError::Schema(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io { path, source } => write!(f, "could not read {}: {}", path.display(), source),
+            LoadError::Sqlite(err) => write!(f, "database error: {}", err),
+            LoadError::Protobuf { path, source } => {
+                write!(f, "invalid Protobuf scenario in {}: {}", path.display(), source)
+            }
+            LoadError::Xml { path, source } => write!(f, "invalid XML scenario in {}: {}", path.display(), source),
+            LoadError::Json { what } => write!(f, "invalid JSON in {}", what),
+            LoadError::Csv { path, source } => write!(f, "could not read {}: {}", path.display(), source),
+            LoadError::Schema(message) => write!(f, "unexpected data layout: {}", message),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for LoadError {
+    fn from(value: rusqlite::Error) -> Self {
+        use rusqlite::types::Type;
+
+        match value {
+            rusqlite::Error::FromSqlConversionFailure(idx, ty, source) => match source.downcast::<LoadError>() {
+                Ok(err) => *err,
+                Err(source) => LoadError::Sqlite(rusqlite::Error::FromSqlConversionFailure(idx, ty, source)),
+            },
+            rusqlite::Error::InvalidColumnName(name) => LoadError::Schema(format!("missing column '{}'", name)),
+            rusqlite::Error::InvalidColumnType(_idx, name, Type::Null) => {
+                LoadError::Schema(format!("unexpected NULL in column '{}'", name))
+            }
+            rusqlite::Error::InvalidColumnType(_idx, name, ty) => {
+                LoadError::Schema(format!("column '{}' has unexpected type {}", name, ty))
+            }
+            err => LoadError::Sqlite(err),
+        }
+    }
+}
+
+/// Reads a JSON encoded text column from a database row.
+pub(crate) fn json_column<T: miniserde::Deserialize>(row: &rusqlite::Row, name: &str) -> rusqlite::Result<T> {
+    let value = row.get_ref(name)?;
+
+    let rusqlite::types::ValueRef::Text(st) = value else {
+        let idx = row.as_ref().column_index(name)?;
+        return Err(rusqlite::Error::InvalidColumnType(idx, name.to_owned(), value.data_type()));
+    };
+
+    let rstr = std::str::from_utf8(st).map_err(rusqlite::Error::Utf8Error)?;
+
+    miniserde::json::from_str(rstr).map_err(|_| LoadError::json(format!("column '{}'", name)).into_sqlite())
+}
+
+/// Problems found while loading that did not prevent the rest of the scene from being shown.
+#[derive(Default, Resource)]
+pub struct LoadDiagnostics {
+    entries: Vec<LoadDiagnostic>,
+    open: bool,
+}
+
+struct LoadDiagnostic {
+    what: String,
+    message: String,
+}
+
+impl LoadDiagnostics {
+    pub(crate) fn warn(&mut self, what: impl Into<String>, err: impl std::fmt::Display) {
+        let what = what.into();
+        bevy::log::warn!("failed to load {}: {}", what, err);
+
+        self.entries.push(LoadDiagnostic {
+            what,
+            message: err.to_string(),
+        });
+        self.open = true;
+    }
+
+    /// Forgets the problems of a previously opened run.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.open = false;
+    }
+
+    /// Records the error of a failed load, returning the loaded value otherwise.
+    pub(crate) fn ok<T, E: std::fmt::Display>(&mut self, what: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.warn(what, err);
+                None
+            }
+        }
+    }
+}
+
+pub fn load_diagnostics_window(
+    mut contexts: EguiContexts,
+    mut diagnostics: ResMut<LoadDiagnostics>,
+) {
+    if diagnostics.entries.is_empty() || !diagnostics.open {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+
+    let LoadDiagnostics { entries, open } = diagnostics.as_mut();
+
+    egui::Window::new("\u{26A0} Load diagnostics")
+        .open(open)
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            ui.label("Some data could not be loaded, the scene is shown without it.");
+            ui.separator();
+
+            for entry in entries.iter() {
+                ui.horizontal_top(|ui| {
+                    ui.label(egui::RichText::new(&entry.what).strong());
+                    ui.label(egui::RichText::new(&entry.message).color(egui::Color32::LIGHT_RED));
+                });
+            }
+        });
+}
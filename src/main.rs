@@ -18,10 +18,20 @@ mod conversion;
 
 pub mod commonroad_pb;
 
+mod commonroad_xml;
+
 pub mod elements;
 
 mod global_settings;
 
+mod loading;
+
+mod runs;
+
+mod scenario;
+
+mod coordinates;
+
 mod args;
 
 mod finite;
@@ -37,14 +47,23 @@ fn main() -> color_eyre::eyre::Result<()> {
 
     let mut app = App::new();
 
-    {
-        // use clap::Parser;
+    let (log_level, live) = {
+        use clap::Parser;
         let args = crate::args::Args::parse();
-        let cr = read_cr(&args);
+        args.validate()?;
+
+        let cr = {
+            use color_eyre::eyre::WrapErr;
+            read_cr(&args).wrap_err("failed to load scenario")?
+        };
+        let log_level = args.log_level.into();
+        let live = args.live;
 
         app.insert_resource(args);
         app.insert_resource(cr);
-    }
+
+        (log_level, live)
+    };
 
     let mut window = Window {
         name: Some(env!("CARGO_PKG_NAME").to_string()),
@@ -57,15 +76,30 @@ fn main() -> color_eyre::eyre::Result<()> {
     };
     window.set_maximized(true);
 
-    app
-        .insert_resource(bevy::winit::WinitSettings {
+    let winit_settings = if live {
+        // New time steps are polled in the background, so keep updating even without any input
+        let update_mode = bevy::winit::UpdateMode::reactive(elements::trajectory::live::POLL_INTERVAL);
+
+        bevy::winit::WinitSettings {
+            focused_mode: update_mode,
+            unfocused_mode: update_mode,
+        }
+    } else {
+        bevy::winit::WinitSettings {
             focused_mode: bevy::winit::UpdateMode::reactive_low_power(Duration::from_secs(5)),
             unfocused_mode: bevy::winit::UpdateMode::reactive_low_power(Duration::from_secs(90)),
-        })
+        }
+    };
+
+    app
+        .insert_resource(winit_settings)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(window),
             exit_condition: bevy::window::ExitCondition::OnPrimaryClosed,
             close_when_requested: true,
+        }).set(bevy::log::LogPlugin {
+            level: log_level,
+            ..default()
         }));
 
     #[cfg(feature = "dev")]
@@ -97,6 +131,10 @@ fn main() -> color_eyre::eyre::Result<()> {
         ;
 
     app.add_plugins(global_settings::GlobalSettingsPlugin)
+        .add_plugins(loading::LoadingPlugin)
+        .add_plugins(runs::RunsPlugin)
+        .add_plugins(scenario::ScenarioPlugin)
+        .add_plugins(coordinates::CoordinatesPlugin)
         .add_plugins(elements::ElementsPlugin)
         .add_plugins(ui::SelectiveInputPlugin)
         .add_systems(Startup, camera_setup)
@@ -249,57 +287,58 @@ fn update_camera_3d(
    }
 }
 
-#[derive(Debug)]
-struct MetaQueryError;
+fn read_cr(args: &crate::args::Args) -> Result<commonroad_pb::CommonRoad, loading::LoadError> {
+    use rusqlite::OptionalExtension;
 
-impl std::error::Error for MetaQueryError {}
-
-impl std::fmt::Display for MetaQueryError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "meta query error")
+    if let Some(scenario) = &args.scenario {
+        return read_cr_file(scenario);
     }
-}
 
-fn read_cr(args: &crate::args::Args) -> commonroad_pb::CommonRoad {
-    let db_path = std::path::Path::join(&args.logs, "trajectories.db");
+    let Some(db_path) = args.trajectories_db() else {
+        unreachable!("clap requires a logs directory unless a scenario file is given");
+    };
     let conn = rusqlite::Connection::open_with_flags(
-        db_path,
+        &db_path,
         rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX
-    ).unwrap();
+    )?;
 
     let data: commonroad_pb::CommonRoad = {
         let mut stmt = conn.prepare(
             "SELECT value FROM meta WHERE key = 'scenario'"
-        ).unwrap();
+        )?;
 
         stmt.query_row([], |row| {
             let rusqlite::types::ValueRef::Blob(st) = row.get_ref(0)? else {
-                return Err(rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(MetaQueryError)));
-             };
+                return Err(loading::LoadError::schema("the 'scenario' meta entry is not a blob").into_sqlite());
+            };
 
-            commonroad_pb::CommonRoad::decode(st).map_err(|err| {
-                return rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(err));
-            })
-        }).unwrap()
+            commonroad_pb::CommonRoad::decode(st).map_err(|err| loading::LoadError::protobuf(&db_path)(err).into_sqlite())
+        })
+        .optional()?
+        .ok_or_else(|| loading::LoadError::schema("the meta table has no 'scenario' entry"))?
     };
 
-    conn.close().unwrap();
+    conn.close().map_err(|(_conn, err)| err)?;
 
-    return data;
+    Ok(data)
+}
 
-    /*
-    mut file: std::fs::File,
-    // TODO: Improve file loading error reporting
-    let f = std::fs::File::open(&args.scenario)?;
-    let mut buffer = Vec::new();
+fn read_cr_file(path: &std::path::Path) -> Result<commonroad_pb::CommonRoad, loading::LoadError> {
+    let is_xml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"));
 
-    // read the whole file
-    file.read_to_end(&mut buffer).unwrap();
+    if is_xml {
+        let input = std::fs::read_to_string(path).map_err(loading::LoadError::io(path))?;
+
+        return commonroad_xml::parse_commonroad(&input).map_err(loading::LoadError::xml(path));
+    }
+
+    let buffer = std::fs::read(path).map_err(loading::LoadError::io(path))?;
 
     let buf = bytes::Bytes::from(buffer);
 
-    commonroad_pb::CommonRoad::decode(buf).unwrap()
-    */
+    commonroad_pb::CommonRoad::decode(buf).map_err(loading::LoadError::protobuf(path))
 }
 
 pub struct CustomDefaultPlugins;
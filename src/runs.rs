@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use bevy_egui::EguiContexts;
+
+use crate::loading::{AppState, LoadDiagnostics};
+
+/// Number of runs remembered in the "Recent runs" menu
+const MAX_RECENT_RUNS: usize = 10;
+
+pub struct RunsPlugin;
+
+impl Plugin for RunsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OpenRunEvent>()
+            .add_systems(Startup, load_recent_runs)
+            .add_systems(Update,
+                (
+                    (menu_bar, pick_folder_result),
+                    open_run,
+                ).chain()
+            );
+    }
+}
+
+/// Replaces the current run with the planner logs in the given directory.
+#[derive(Event)]
+pub(crate) struct OpenRunEvent(pub(crate) std::path::PathBuf);
+
+/// Log directories opened before, most recent first.
+#[derive(Default, Resource)]
+pub(crate) struct RecentRuns {
+    runs: Vec<std::path::PathBuf>,
+}
+
+#[derive(Resource)]
+struct PickFolderTask(Task<Option<std::path::PathBuf>>);
+
+fn recent_runs_path() -> Option<std::path::PathBuf> {
+    Some(dirs::data_local_dir()?.join(env!("CARGO_PKG_NAME")).join("recent_runs"))
+}
+
+impl RecentRuns {
+    fn load() -> Self {
+        let Some(path) = recent_runs_path() else {
+            return Self::default();
+        };
+
+        let runs = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(std::path::PathBuf::from)
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(err) => {
+                bevy::log::warn!("could not read recent runs from {}: {}", path.display(), err);
+                vec![]
+            }
+        };
+
+        Self { runs }
+    }
+
+    fn save(&self) {
+        let Some(path) = recent_runs_path() else {
+            return;
+        };
+
+        let contents: String = self
+            .runs
+            .iter()
+            .map(|run| format!("{}\n", run.display()))
+            .collect();
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, contents));
+
+        if let Err(err) = result {
+            bevy::log::warn!("could not save recent runs to {}: {}", path.display(), err);
+        }
+    }
+
+    fn add(&mut self, run: &std::path::Path) {
+        let run = std::fs::canonicalize(run).unwrap_or_else(|_| run.to_owned());
+
+        self.runs.retain(|existing| *existing != run);
+        self.runs.insert(0, run);
+        self.runs.truncate(MAX_RECENT_RUNS);
+
+        self.save();
+    }
+}
+
+fn load_recent_runs(mut commands: Commands, args: Res<crate::args::Args>) {
+    let mut recent_runs = RecentRuns::load();
+
+    if let Some(logs) = &args.logs {
+        recent_runs.add(logs);
+    }
+
+    commands.insert_resource(recent_runs);
+}
+
+fn menu_bar(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    recent_runs: Res<RecentRuns>,
+    pick_folder_task: Option<Res<PickFolderTask>>,
+    args: Res<crate::args::Args>,
+    mut open_run_events: EventWriter<OpenRunEvent>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    egui::TopBottomPanel::top("menu bar").show(ctx, |ui| {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                let open_button = egui::Button::new("Open log directory\u{2026}");
+                if ui.add_enabled(pick_folder_task.is_none(), open_button).clicked() {
+                    let mut dialog = rfd::AsyncFileDialog::new().set_title("Open log directory");
+                    if let Some(logs) = &args.logs {
+                        dialog = dialog.set_directory(logs);
+                    }
+
+                    let task = AsyncComputeTaskPool::get().spawn(async move {
+                        let folder = dialog.pick_folder().await?;
+
+                        Some(folder.path().to_owned())
+                    });
+                    commands.insert_resource(PickFolderTask(task));
+
+                    ui.close_menu();
+                }
+
+                ui.add_enabled_ui(!recent_runs.runs.is_empty(), |ui| {
+                    ui.menu_button("Recent runs", |ui| {
+                        for run in recent_runs.runs.iter() {
+                            if ui.button(run.display().to_string()).clicked() {
+                                open_run_events.send(OpenRunEvent(run.clone()));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                });
+            });
+        });
+    });
+}
+
+fn pick_folder_result(
+    mut commands: Commands,
+    task: Option<ResMut<PickFolderTask>>,
+    mut open_run_events: EventWriter<OpenRunEvent>,
+    mut redraw_request_events: EventWriter<bevy::window::RequestRedraw>,
+) {
+    let Some(mut task) = task else {
+        return;
+    };
+
+    let Some(folder) = bevy::tasks::block_on(bevy::tasks::poll_once(&mut task.0)) else {
+        // The dialog does not wake up the event loop once it is closed
+        redraw_request_events.send(bevy::window::RequestRedraw);
+        return;
+    };
+
+    commands.remove_resource::<PickFolderTask>();
+
+    if let Some(folder) = folder {
+        open_run_events.send(OpenRunEvent(folder));
+    }
+}
+
+/// Reads the scenario of the newly opened run, the rest is loaded once the previous run is unloaded.
+fn open_run(
+    mut commands: Commands,
+    mut open_run_events: EventReader<OpenRunEvent>,
+    args: Res<crate::args::Args>,
+    mut recent_runs: ResMut<RecentRuns>,
+    mut diagnostics: ResMut<LoadDiagnostics>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(OpenRunEvent(logs)) = open_run_events.read().last() else {
+        return;
+    };
+
+    bevy::log::info!("opening run {}", logs.display());
+
+    let new_args = crate::args::Args {
+        logs: Some(logs.clone()),
+        scenario: None,
+        reference_path: None,
+        live: args.live,
+        time_step: 0,
+        log_level: args.log_level,
+    };
+
+    // The current run stays open if the new one cannot be read
+    let what = format!("run {}", logs.display());
+    if let Err(err) = new_args.validate() {
+        diagnostics.warn(what, format!("{:#}", err));
+        return;
+    }
+    let Some(cr) = diagnostics.ok(&what, crate::read_cr(&new_args)) else {
+        return;
+    };
+
+    recent_runs.add(logs);
+
+    commands.insert_resource(new_args);
+    commands.insert_resource(cr);
+
+    next_state.set(AppState::Unloaded);
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use bevy_egui::EguiContexts;
+
+use crate::commonroad_pb;
+
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, scenario_window);
+    }
+}
+
+/// Number of scenario elements by their type.
+#[derive(Default)]
+struct ElementCounts {
+    lanelets: BTreeMap<String, usize>,
+    obstacles: BTreeMap<String, usize>,
+    traffic_signs: BTreeMap<String, usize>,
+    traffic_lights: BTreeMap<String, usize>,
+}
+
+impl ElementCounts {
+    fn new(cr: &commonroad_pb::CommonRoad) -> Self {
+        fn count(counts: &mut BTreeMap<String, usize>, key: impl Into<String>) {
+            *counts.entry(key.into()).or_default() += 1;
+        }
+
+        let mut counts = Self::default();
+
+        for lanelet in &cr.lanelets {
+            if lanelet.lanelet_types.is_empty() {
+                count(&mut counts.lanelets, "untyped");
+            }
+            for lanelet_type in lanelet.lanelet_types() {
+                count(&mut counts.lanelets, format!("{:?}", lanelet_type));
+            }
+        }
+
+        for obs in &cr.dynamic_obstacles {
+            count(&mut counts.obstacles, format!("dynamic {:?}", obs.obstacle_type()));
+        }
+        for obs in &cr.static_obstacles {
+            count(&mut counts.obstacles, format!("static {:?}", obs.obstacle_type()));
+        }
+        for obs in &cr.environment_obstacles {
+            count(&mut counts.obstacles, format!("environment {:?}", obs.obstacle_type()));
+        }
+        if !cr.phantom_obstacles.is_empty() {
+            counts.obstacles.insert("phantom".to_owned(), cr.phantom_obstacles.len());
+        }
+
+        for sign in &cr.traffic_signs {
+            for element in &sign.traffic_sign_elements {
+                let name = crate::elements::traffic_sign::element_name(element).unwrap_or("UNKNOWN");
+
+                if sign.r#virtual == Some(true) {
+                    count(&mut counts.traffic_signs, format!("{} (virtual)", name));
+                } else {
+                    count(&mut counts.traffic_signs, name);
+                }
+            }
+        }
+
+        for light in &cr.traffic_lights {
+            let direction = match light.direction {
+                Some(_) => format!("{:?}", light.direction()),
+                None => "no direction".to_owned(),
+            };
+
+            if light.active == Some(false) {
+                count(&mut counts.traffic_lights, format!("{} (inactive)", direction));
+            } else {
+                count(&mut counts.traffic_lights, direction);
+            }
+        }
+
+        counts
+    }
+}
+
+fn format_time_stamp(time: &commonroad_pb::TimeStamp) -> String {
+    let date = match (time.year, time.month, time.day) {
+        (Some(year), Some(month), Some(day)) => format!("{:04}-{:02}-{:02}", year, month, day),
+        _ => "unknown date".to_owned(),
+    };
+
+    match (time.hour, time.minute) {
+        (Some(hour), Some(minute)) => format!("{} {:02}:{:02}", date, hour, minute),
+        _ => date,
+    }
+}
+
+fn info_grid(ui: &mut egui::Ui, id: &str, rows: &[(&str, String)]) {
+    egui::Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+        for (key, value) in rows {
+            ui.label(egui::RichText::new(*key).strong());
+            ui.label(value);
+            ui.end_row();
+        }
+    });
+}
+
+fn count_section(ui: &mut egui::Ui, title: &str, total: usize, counts: &BTreeMap<String, usize>) {
+    egui::CollapsingHeader::new(format!("{} ({})", title, total))
+        .id_source(title)
+        .show(ui, |ui| {
+            egui::Grid::new(title).num_columns(2).striped(true).show(ui, |ui| {
+                for (key, value) in counts {
+                    ui.label(key);
+                    ui.label(value.to_string());
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn scenario_window(
+    mut contexts: EguiContexts,
+    cr: Res<crate::CommonRoad>,
+    mut counts: Local<Option<ElementCounts>>,
+) {
+    if cr.is_changed() {
+        *counts = None;
+    }
+
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new("Scenario")
+        .default_open(false)
+        .default_width(350.0)
+        .show(ctx, |ui| {
+            let info = &cr.information;
+
+            ui.heading(&info.benchmark_id);
+            info_grid(
+                ui,
+                "scenario information",
+                &[
+                    ("author", info.author.clone()),
+                    ("affiliation", info.affiliation.clone()),
+                    ("source", info.source.clone()),
+                    ("date", format_time_stamp(&info.date)),
+                    ("time step size", format!("{} s", info.time_step_size)),
+                    ("CommonRoad version", info.common_road_version.clone()),
+                ],
+            );
+
+            let tags: Vec<String> = cr.scenario_tags.tags().map(|tag| format!("{:?}", tag)).collect();
+            ui.label(format!("tags: {}", tags.join(", ")));
+
+            ui.separator();
+            ui.heading("Location");
+
+            let location = &cr.location;
+            let mut rows = vec![
+                ("geo name id", location.geo_name_id.to_string()),
+                (
+                    "GPS",
+                    format!("{:.6}, {:.6}", location.gps_latitude, location.gps_longitude),
+                ),
+            ];
+            if let Some(environment) = &location.environment {
+                rows.extend([
+                    (
+                        "time",
+                        environment.time.as_ref().map_or("unknown".to_owned(), format_time_stamp),
+                    ),
+                    ("time of day", format!("{:?}", environment.time_of_day())),
+                    ("weather", format!("{:?}", environment.weather())),
+                    ("underground", format!("{:?}", environment.underground())),
+                ]);
+            }
+            info_grid(ui, "scenario location", &rows);
+
+            ui.separator();
+            ui.heading("Elements");
+
+            let counts = counts.get_or_insert_with(|| ElementCounts::new(&cr));
+            // Lanelets can have several types and signs several elements
+            count_section(ui, "Lanelets", cr.lanelets.len(), &counts.lanelets);
+            count_section(ui, "Obstacles", counts.obstacles.values().sum(), &counts.obstacles);
+            count_section(ui, "Traffic signs", cr.traffic_signs.len(), &counts.traffic_signs);
+            count_section(ui, "Traffic lights", cr.traffic_lights.len(), &counts.traffic_lights);
+        });
+}